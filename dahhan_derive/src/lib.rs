@@ -0,0 +1,129 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Index, Member, parse_macro_input, parse_quote};
+
+/// Implements `dahhan::ecs::reflect::Reflect` for a struct, exposing its fields by name
+///
+/// Tuple struct fields are named by their index, fields marked with `#[reflect(ignore)]` are
+/// skipped and don't need to implement `Reflect`. `#[reflect(default)]` on the struct exposes its
+/// `Default` impl, which lets scenes create the type
+#[proc_macro_derive(Reflect, attributes(reflect))]
+pub fn derive_reflect(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match reflect_impl(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn reflect_impl(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "Reflect can only be derived for structs",
+        ));
+    };
+
+    let has_default = reflect_attributes(&input.attrs, &["default"])?.contains(&"default");
+
+    let mut members = Vec::new();
+    let mut names = Vec::new();
+    let fields = match &data.fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    };
+    for (index, field) in fields.into_iter().enumerate() {
+        if !reflect_attributes(&field.attrs, &["ignore"])?.is_empty() {
+            continue;
+        }
+
+        match &field.ident {
+            Some(ident) => {
+                names.push(ident.to_string());
+                members.push(Member::Named(ident.clone()));
+            }
+            None => {
+                names.push(index.to_string());
+                members.push(Member::Unnamed(Index::from(index)));
+            }
+        }
+    }
+
+    let reflect: syn::Path = parse_quote!(::dahhan::ecs::reflect::Reflect);
+    for param in input.generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#reflect));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let reflect_default = has_default.then(|| {
+        quote! {
+            fn reflect_default() -> Option<Box<dyn #reflect>>
+            where
+                Self: Sized,
+            {
+                Some(Box::new(<Self as ::core::default::Default>::default()))
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics #reflect for #ident #type_generics #where_clause {
+            #reflect_default
+
+            fn type_field_names() -> &'static [&'static str]
+            where
+                Self: Sized,
+            {
+                &[#(#names),*]
+            }
+
+            fn field_names(&self) -> &'static [&'static str] {
+                &[#(#names),*]
+            }
+
+            fn field(&self, name: &str) -> Option<&dyn #reflect> {
+                match name {
+                    #(#names => Some(&self.#members),)*
+                    _ => None,
+                }
+            }
+
+            fn field_mut(&mut self, name: &str) -> Option<&mut dyn #reflect> {
+                match name {
+                    #(#names => Some(&mut self.#members),)*
+                    _ => None,
+                }
+            }
+        }
+    })
+}
+
+/// The `#[reflect(...)]` arguments, only the `allowed` ones are accepted
+fn reflect_attributes(
+    attrs: &[syn::Attribute],
+    allowed: &[&'static str],
+) -> syn::Result<Vec<&'static str>> {
+    let mut found = Vec::new();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("reflect")) {
+        attr.parse_nested_meta(|meta| {
+            match allowed.iter().find(|name| meta.path.is_ident(name)) {
+                Some(name) => {
+                    found.push(*name);
+                    Ok(())
+                }
+                None => Err(meta.error(format!(
+                    "unknown reflect attribute, expected one of: {}",
+                    allowed.join(", ")
+                ))),
+            }
+        })?;
+    }
+
+    Ok(found)
+}
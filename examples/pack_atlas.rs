@@ -0,0 +1,36 @@
+//! Packs loose PNG images into one atlas image and a JSON layout
+//!
+//! cargo run --example pack_atlas -- atlas.png frame0.png frame1.png ...
+//!
+//! The layout is saved next to the atlas as `atlas.json` and loaded with
+//! `TextureAtlasLayout::load`, its indices follow the order of the input images
+
+use dahhan::prelude::*;
+
+fn main() -> Result<(), anyhow::Error> {
+    let mut args = std::env::args().skip(1);
+    let output = args
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("Usage: pack_atlas <output.png> <images>..."))?;
+
+    let mut builder = TextureAtlasBuilder::new().padding(1);
+    for path in args {
+        let bytes =
+            std::fs::read(&path).map_err(|e| anyhow::anyhow!("Failed to read {path}: {e}"))?;
+        let index = builder.add_image(Image::from_bytes(&bytes)?);
+        println!("{index}: {path}");
+    }
+
+    let (atlas, layout) = builder.build()?;
+    atlas.save(&output)?;
+    layout.save(std::path::Path::new(&output).with_extension("json"))?;
+
+    println!(
+        "Packed {} images into a {}x{} atlas",
+        layout.len(),
+        atlas.width,
+        atlas.height
+    );
+
+    Ok(())
+}
@@ -1,9 +1,12 @@
 use dahhan::{App, WindowResized, camera::Camera, ecs::Component, prelude::*};
-use glam::{Vec2, Vec3};
+use glam::{Vec2, Vec3, Vec4};
 
 struct Background;
 impl Component for Background {}
 
+struct ScoreText;
+impl Component for ScoreText {}
+
 struct Ball {
     is_going_up: bool,
     is_going_right: bool,
@@ -20,23 +23,29 @@ struct Player2 {
 }
 impl Component for Player2 {}
 
-fn move_player1(player1: Query<(&Player1, &mut Transform)>, input: Res<Input>, time: Res<Time>) {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Axis {
+    Player1,
+    Player2,
+}
+
+fn move_player1(
+    player1: Query<(&Player1, &mut Transform)>,
+    actions: Actions<(), Axis>,
+    time: Res<Time>,
+) {
     for (_e, (_player, transform)) in player1.iter() {
-        if input.is_pressed(KeyCode::KeyW) {
-            transform.position.y += 10000. * time.delta_time();
-        } else if input.is_pressed(KeyCode::KeyS) {
-            transform.position.y -= 10000. * time.delta_time();
-        }
+        transform.position.y += actions.axis(Axis::Player1) * 10000. * time.delta_time();
     }
 }
 
-fn move_player2(player1: Query<(&Player2, &mut Transform)>, input: Res<Input>, time: Res<Time>) {
-    for (_e, (_player, transform)) in player1.iter() {
-        if input.is_pressed(KeyCode::ArrowUp) {
-            transform.position.y += 10000. * time.delta_time();
-        } else if input.is_pressed(KeyCode::ArrowDown) {
-            transform.position.y -= 10000. * time.delta_time();
-        }
+fn move_player2(
+    player2: Query<(&Player2, &mut Transform)>,
+    actions: Actions<(), Axis>,
+    time: Res<Time>,
+) {
+    for (_e, (_player, transform)) in player2.iter() {
+        transform.position.y += actions.axis(Axis::Player2) * 10000. * time.delta_time();
     }
 }
 
@@ -129,9 +138,44 @@ fn resize_background(
     }
 }
 
+fn update_score_text(
+    score_text: Query<(&ScoreText, &mut Text2d)>,
+    player1: Query<&Player1>,
+    player2: Query<&Player2>,
+    window: Res<Window>,
+) {
+    if let Some((_e, (_score_text, text))) = score_text.iter().next()
+        && let Some((_, player1)) = player1.iter().next()
+        && let Some((_, player2)) = player2.iter().next()
+    {
+        text.sections[0].text = player1.score.to_string();
+        text.sections[2].text = player2.score.to_string();
+        text.bounds = Some(Vec2::new(window.width, 60.));
+    }
+}
+
 fn main() {
     let mut app = App::new();
 
+    app.insert_resource(WindowDescriptor {
+        title: "Pong".to_string(),
+        width: 1000.,
+        height: 600.,
+        ..Default::default()
+    });
+
+    let mut actions = ActionMap::<(), Axis>::new();
+    actions
+        .bind_axis(
+            Axis::Player1,
+            AxisBinding::one_d(KeyCode::KeyS, KeyCode::KeyW),
+        )
+        .bind_axis(
+            Axis::Player2,
+            AxisBinding::one_d(KeyCode::ArrowDown, KeyCode::ArrowUp),
+        );
+    app.insert_resource(actions);
+
     app.add_entity((
         Camera::default_2d(),
         Transform {
@@ -143,6 +187,7 @@ fn main() {
 
     app = app
         .register_component::<Background>()
+        .register_component::<ScoreText>()
         .register_component::<Ball>()
         .register_component::<Player1>()
         .register_component::<Player2>();
@@ -150,12 +195,13 @@ fn main() {
     app.add_entity((
         Background,
         Sprite {
-            texture_id: None,
             size: Vec2::new(50., 200.),
-            color: Vec3::new(0.1, 0.1, 0.1),
+            color: Vec4::new(0.1, 0.1, 0.1, 1.),
+            ..Default::default()
         },
         Transform {
-            position: Vec3::new(400., 100., 0.),
+            // Behind the paddles and the ball
+            position: Vec3::new(400., 100., -1.),
             rotation: 0.,
             scale: Vec2::splat(1.),
         },
@@ -167,9 +213,9 @@ fn main() {
             is_going_right: true,
         },
         Sprite {
-            texture_id: None,
             size: Vec2::splat(50.),
-            color: Vec3::new(0.5, 0.5, 0.5),
+            color: Vec4::new(0.5, 0.5, 0.5, 1.),
+            ..Default::default()
         },
         Transform {
             position: Vec3::new(-25., 25., 0.),
@@ -178,12 +224,12 @@ fn main() {
         },
     ));
 
-    app.add_entity((
+    let player1 = app.add_entity((
         Player1 { score: 0 },
         Sprite {
-            texture_id: None,
             size: Vec2::new(50., 200.),
-            color: Vec3::new(1., 0.5, 0.5),
+            color: Vec4::new(1., 0.5, 0.5, 1.),
+            ..Default::default()
         },
         Transform {
             position: Vec3::new(-450., 100., 0.),
@@ -192,12 +238,12 @@ fn main() {
         },
     ));
 
-    app.add_entity((
+    let player2 = app.add_entity((
         Player2 { score: 0 },
         Sprite {
-            texture_id: None,
             size: Vec2::new(50., 200.),
-            color: Vec3::new(0., 0.5, 0.5),
+            color: Vec4::new(0., 0.5, 0.5, 1.),
+            ..Default::default()
         },
         Transform {
             position: Vec3::new(400., 100., 0.),
@@ -206,13 +252,59 @@ fn main() {
         },
     ));
 
+    // A glow behind each paddle that follows it around
+    for (player, color) in [
+        (player1, Vec4::new(1., 0.5, 0.5, 0.3)),
+        (player2, Vec4::new(0., 0.5, 0.5, 0.3)),
+    ] {
+        let glow = app.add_entity((
+            Sprite {
+                size: Vec2::new(70., 220.),
+                color,
+                ..Default::default()
+            },
+            Transform {
+                position: Vec3::new(-10., 10., -0.5),
+                rotation: 0.,
+                scale: Vec2::splat(1.),
+            },
+        ));
+        app.world_mut().set_parent(glow, player);
+    }
+
+    app.add_entity((
+        ScoreText,
+        Text2d {
+            sections: vec![
+                TextSection {
+                    color: Vec4::new(1., 0.5, 0.5, 1.),
+                    ..TextSection::new("0", 48.)
+                },
+                TextSection::new("  -  ", 48.),
+                TextSection {
+                    color: Vec4::new(0., 0.5, 0.5, 1.),
+                    ..TextSection::new("0", 48.)
+                },
+            ],
+            align: TextAlign::Center,
+            bounds: Some(Vec2::new(1000., 60.)),
+            space: TextSpace::Screen(WindowRef::Primary),
+        },
+        Transform {
+            position: Vec3::new(0., 10., 0.),
+            rotation: 0.,
+            scale: Vec2::splat(1.),
+        },
+    ));
+
     app = app
         .add_system(move_player1)
         .add_system(move_player2)
         .add_system(ball_collision)
         .add_system(ball_scoring)
         .add_system(move_ball)
-        .add_system(resize_background);
+        .add_system(resize_background)
+        .add_system(update_score_text);
 
     app.run().unwrap();
 }
@@ -0,0 +1,243 @@
+use std::ops::Range;
+
+use crate::ecs::{Component, entity::Entity, events::Event};
+
+/// What happens when an animation reaches its last frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnimationMode {
+    /// Stops on the last frame and sends [`AnimationFinished`]
+    Once,
+    #[default]
+    Loop,
+    /// Plays backwards back to the first frame, then forwards again
+    PingPong,
+}
+
+/// Steps the index of the [`AtlasSprite`](crate::atlas::AtlasSprite) on the same entity through
+/// a range of atlas frames, driven by [`Time`](crate::time::Time)
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteAnimation {
+    /// Atlas indices played in order
+    pub frames: Range<usize>,
+    pub mode: AnimationMode,
+    pub paused: bool,
+    /// Seconds every frame is shown for, one per frame
+    durations: Vec<f32>,
+    /// Offset into `frames`
+    frame: usize,
+    elapsed: f32,
+    reversed: bool,
+    finished: bool,
+}
+
+impl SpriteAnimation {
+    /// Shows every frame for `frame_duration` seconds
+    pub fn new(frames: Range<usize>, frame_duration: f32, mode: AnimationMode) -> Self {
+        let durations = vec![frame_duration; frames.len()];
+
+        Self::with_durations(frames, durations, mode)
+    }
+
+    /// Shows every frame for its own duration in seconds
+    pub fn with_durations(frames: Range<usize>, durations: Vec<f32>, mode: AnimationMode) -> Self {
+        assert_eq!(
+            frames.len(),
+            durations.len(),
+            "Every frame of an animation needs a duration"
+        );
+        assert!(
+            durations.iter().all(|duration| *duration > 0.),
+            "Frame durations must be positive"
+        );
+
+        Self {
+            frames,
+            mode,
+            paused: false,
+            durations,
+            frame: 0,
+            elapsed: 0.,
+            reversed: false,
+            finished: false,
+        }
+    }
+
+    /// The atlas index of the frame currently shown
+    pub fn current_index(&self) -> usize {
+        self.frames.start + self.frame
+    }
+
+    /// Only [`AnimationMode::Once`] animations finish
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Restarts the animation from its first frame
+    pub fn reset(&mut self) {
+        self.frame = 0;
+        self.elapsed = 0.;
+        self.reversed = false;
+        self.finished = false;
+    }
+
+    /// Moves the animation forward by `delta` seconds, returning whether it finished during it
+    pub fn advance(&mut self, delta: f32) -> bool {
+        if self.paused || self.finished || self.frames.is_empty() {
+            return false;
+        }
+
+        self.elapsed += delta;
+
+        while self.elapsed >= self.durations[self.frame] {
+            self.elapsed -= self.durations[self.frame];
+
+            if self.step() {
+                self.finished = true;
+                self.elapsed = 0.;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Goes to the next frame, returning whether there is none
+    fn step(&mut self) -> bool {
+        let last = self.frames.len() - 1;
+
+        if self.reversed {
+            if self.frame > 0 {
+                self.frame -= 1;
+            } else {
+                self.reversed = false;
+                self.frame = last.min(1);
+            }
+
+            return false;
+        }
+
+        if self.frame < last {
+            self.frame += 1;
+            return false;
+        }
+
+        match self.mode {
+            AnimationMode::Once => true,
+            AnimationMode::Loop => {
+                self.frame = 0;
+                false
+            }
+            AnimationMode::PingPong => {
+                self.reversed = true;
+                self.frame = last.saturating_sub(1);
+                false
+            }
+        }
+    }
+}
+
+impl Component for SpriteAnimation {}
+
+/// Sent when an [`AnimationMode::Once`] animation reaches the end of its last frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnimationFinished {
+    pub entity: Entity,
+}
+
+impl Event for AnimationFinished {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        App,
+        atlas::{AtlasSprite, TextureAtlasLayout, TextureAtlasLayouts},
+        ecs::{events::EventReader, scheduler::ResMut},
+        input::replay::{InputRecording, RecordedFrame},
+    };
+
+    use super::*;
+
+    fn play(animation: &mut SpriteAnimation, steps: usize, delta: f32) -> Vec<usize> {
+        (0..steps)
+            .map(|_| {
+                animation.advance(delta);
+                animation.current_index()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_animation_modes() {
+        let mut animation = SpriteAnimation::new(4..7, 0.1, AnimationMode::Loop);
+        assert_eq!(animation.current_index(), 4);
+        assert_eq!(play(&mut animation, 4, 0.1), vec![5, 6, 4, 5]);
+
+        let mut animation = SpriteAnimation::new(0..3, 0.1, AnimationMode::PingPong);
+        assert_eq!(play(&mut animation, 6, 0.1), vec![1, 2, 1, 0, 1, 2]);
+
+        let mut animation = SpriteAnimation::new(0..2, 0.1, AnimationMode::Once);
+        assert!(!animation.advance(0.1));
+        assert!(animation.advance(0.1));
+        assert!(animation.is_finished());
+        assert!(!animation.advance(0.1));
+        assert_eq!(animation.current_index(), 1);
+
+        animation.reset();
+        assert_eq!(animation.current_index(), 0);
+        assert!(!animation.is_finished());
+    }
+
+    #[test]
+    fn test_animation_frame_durations() {
+        let mut animation =
+            SpriteAnimation::with_durations(0..3, vec![0.5, 0.1, 0.2], AnimationMode::Loop);
+
+        assert_eq!(play(&mut animation, 3, 0.25), vec![0, 1, 2]);
+
+        // Large deltas skip frames
+        animation.reset();
+        animation.advance(0.65);
+        assert_eq!(animation.current_index(), 2);
+
+        animation.paused = true;
+        animation.advance(1.);
+        assert_eq!(animation.current_index(), 2);
+    }
+
+    fn collect_finished(
+        mut finished: EventReader<AnimationFinished>,
+        mut entities: ResMut<Vec<Entity>>,
+    ) {
+        entities.extend(finished.read().map(|event| event.entity));
+    }
+
+    #[test]
+    fn test_animate_atlas_sprite() {
+        let mut app = App::new().add_system(collect_finished);
+        app.insert_resource(Vec::<Entity>::new());
+
+        let layout = app
+            .world_mut()
+            .write_resource::<TextureAtlasLayouts>()
+            .unwrap()
+            .add(TextureAtlasLayout::new(glam::UVec2::ONE));
+        let entity = app.add_entity((
+            AtlasSprite::new(layout, 0),
+            SpriteAnimation::new(2..4, 0.5, AnimationMode::Once),
+        ));
+
+        let mut recording = InputRecording::new();
+        for frame in 1..=3 {
+            recording.push_frame(RecordedFrame {
+                frame,
+                delta: 0.5,
+                events: vec![],
+            });
+        }
+        app.replay(&recording);
+
+        let world = app.world_mut();
+        assert_eq!(world.get_component::<AtlasSprite>(entity).unwrap().index, 3);
+        assert_eq!(*world.read_resource::<Vec<Entity>>().unwrap(), vec![entity]);
+    }
+}
@@ -0,0 +1,335 @@
+use std::{collections::HashMap, path::Path};
+
+use glam::{UVec2, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ecs::{Component, rendering::Rect},
+    texture::Image,
+};
+
+/// Refers to a layout in the [`TextureAtlasLayouts`] resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AtlasLayoutHandle(u32);
+
+/// The regions of a texture that each hold a separate image, like the frames of a sprite sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureAtlasLayout {
+    /// Size of the whole texture in pixels
+    pub size: UVec2,
+    /// Regions in pixels, the top-left corner of the texture is the origin
+    pub rects: Vec<Rect>,
+}
+
+/// How a layout is stored on disk, [`Rect`] isn't serializable
+#[derive(Serialize, Deserialize)]
+struct LayoutFile {
+    size: [u32; 2],
+    rects: Vec<[f32; 4]>,
+}
+
+impl TextureAtlasLayout {
+    pub fn new(size: UVec2) -> Self {
+        Self {
+            size,
+            rects: Vec::new(),
+        }
+    }
+
+    /// Splits a sprite sheet into `columns * rows` tiles, indexed row by row from the top-left
+    ///
+    /// `padding` is the space between tiles and `offset` the space before the first one
+    pub fn from_grid(
+        tile_size: UVec2,
+        columns: u32,
+        rows: u32,
+        padding: UVec2,
+        offset: UVec2,
+    ) -> Self {
+        let mut layout = Self::new(UVec2::ZERO);
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let min = offset + (tile_size + padding) * UVec2::new(column, row);
+                layout.size = layout.size.max(min + tile_size);
+                layout.add_rect(Rect {
+                    min: min.as_vec2(),
+                    max: (min + tile_size).as_vec2(),
+                });
+            }
+        }
+
+        layout
+    }
+
+    /// Returns the index of the new region
+    pub fn add_rect(&mut self, rect: Rect) -> usize {
+        self.rects.push(rect);
+
+        self.rects.len() - 1
+    }
+
+    pub fn get(&self, index: usize) -> Option<Rect> {
+        self.rects.get(index).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, anyhow::Error> {
+        let file: LayoutFile = serde_json::from_str(json)?;
+
+        Ok(Self {
+            size: file.size.into(),
+            rects: file
+                .rects
+                .into_iter()
+                .map(|[x, y, width, height]| Rect::new(x, y, width, height))
+                .collect(),
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, anyhow::Error> {
+        let file = LayoutFile {
+            size: self.size.into(),
+            rects: self
+                .rects
+                .iter()
+                .map(|rect| {
+                    let size = rect.size();
+                    [rect.min.x, rect.min.y, size.x, size.y]
+                })
+                .collect(),
+        };
+
+        Ok(serde_json::to_string_pretty(&file)?)
+    }
+
+    /// Loads a layout saved with [`TextureAtlasLayout::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+
+        Self::from_json(&json)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+        let path = path.as_ref();
+
+        std::fs::write(path, self.to_json()?)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {e}", path.display()))
+    }
+}
+
+/// Layouts used by [`AtlasSprite`]s
+pub struct TextureAtlasLayouts {
+    layouts: HashMap<AtlasLayoutHandle, TextureAtlasLayout>,
+    next_id: u32,
+}
+
+impl TextureAtlasLayouts {
+    pub(crate) fn new() -> Self {
+        Self {
+            layouts: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn add(&mut self, layout: TextureAtlasLayout) -> AtlasLayoutHandle {
+        let handle = AtlasLayoutHandle(self.next_id);
+        self.next_id += 1;
+        self.layouts.insert(handle, layout);
+
+        handle
+    }
+
+    pub fn get(&self, handle: AtlasLayoutHandle) -> Option<&TextureAtlasLayout> {
+        self.layouts.get(&handle)
+    }
+
+    pub fn get_mut(&mut self, handle: AtlasLayoutHandle) -> Option<&mut TextureAtlasLayout> {
+        self.layouts.get_mut(&handle)
+    }
+
+    pub fn remove(&mut self, handle: AtlasLayoutHandle) -> Option<TextureAtlasLayout> {
+        self.layouts.remove(&handle)
+    }
+}
+
+/// Draws one region of an atlas, the [`Sprite`](crate::ecs::rendering::Sprite) on the same
+/// entity has its `rect` replaced by it before rendering
+///
+/// The sprite `texture` should be the atlas image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasSprite {
+    pub layout: AtlasLayoutHandle,
+    pub index: usize,
+}
+
+impl AtlasSprite {
+    pub fn new(layout: AtlasLayoutHandle, index: usize) -> Self {
+        Self { layout, index }
+    }
+
+    /// The region to draw, `None` if the layout or index doesn't exist
+    pub fn rect(&self, layouts: &TextureAtlasLayouts) -> Option<Rect> {
+        layouts.get(self.layout)?.get(self.index)
+    }
+}
+
+impl Component for AtlasSprite {}
+
+/// Packs loose images into a single atlas image, meant to be run ahead of time with the result
+/// saved next to the other assets
+///
+/// Images are placed in rows from the tallest to the shortest
+#[derive(Debug, Clone)]
+pub struct TextureAtlasBuilder {
+    images: Vec<Image>,
+    max_width: u32,
+    padding: u32,
+}
+
+impl Default for TextureAtlasBuilder {
+    fn default() -> Self {
+        Self {
+            images: Vec::new(),
+            max_width: 2048,
+            padding: 0,
+        }
+    }
+}
+
+impl TextureAtlasBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The atlas never gets wider than this, it grows downwards instead
+    pub fn max_width(mut self, max_width: u32) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Space between images, avoids bleeding when sampling near the edges
+    pub fn padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Returns the index the image will have in the layout
+    pub fn add_image(&mut self, image: Image) -> usize {
+        self.images.push(image);
+
+        self.images.len() - 1
+    }
+
+    pub fn build(&self) -> Result<(Image, TextureAtlasLayout), anyhow::Error> {
+        if let Some(image) = self.images.iter().find(|i| i.width > self.max_width) {
+            anyhow::bail!(
+                "An image is {} pixels wide but the atlas is at most {} pixels wide",
+                image.width,
+                self.max_width
+            );
+        }
+
+        let mut order: Vec<usize> = (0..self.images.len()).collect();
+        order.sort_by_key(|&index| std::cmp::Reverse(self.images[index].height));
+
+        let mut positions = vec![UVec2::ZERO; self.images.len()];
+        let mut cursor = UVec2::ZERO;
+        let mut row_height = 0;
+        let mut size = UVec2::ZERO;
+
+        for index in order {
+            let image = &self.images[index];
+
+            if cursor.x > 0 && cursor.x + image.width > self.max_width {
+                cursor = UVec2::new(0, cursor.y + row_height + self.padding);
+                row_height = 0;
+            }
+
+            positions[index] = cursor;
+            size = size.max(cursor + UVec2::new(image.width, image.height));
+            row_height = row_height.max(image.height);
+            cursor.x += image.width + self.padding;
+        }
+
+        let mut data = vec![0; (size.x * size.y * 4) as usize];
+        let mut layout = TextureAtlasLayout::new(size);
+
+        for (image, position) in self.images.iter().zip(positions) {
+            let row_bytes = (image.width * 4) as usize;
+            for row in 0..image.height {
+                let src = (row * image.width * 4) as usize;
+                let dst = (((position.y + row) * size.x + position.x) * 4) as usize;
+                data[dst..dst + row_bytes].copy_from_slice(&image.data[src..src + row_bytes]);
+            }
+
+            layout.add_rect(Rect {
+                min: position.as_vec2(),
+                max: position.as_vec2() + Vec2::new(image.width as f32, image.height as f32),
+            });
+        }
+
+        Ok((Image::new(size.x, size.y, data), layout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_layout() {
+        let layout =
+            TextureAtlasLayout::from_grid(UVec2::new(16, 8), 3, 2, UVec2::ONE, UVec2::new(2, 0));
+
+        assert_eq!(layout.len(), 6);
+        assert_eq!(layout.size, UVec2::new(2 + 3 * 16 + 2, 2 * 8 + 1));
+        assert_eq!(layout.get(0), Some(Rect::new(2., 0., 16., 8.)));
+        assert_eq!(layout.get(4), Some(Rect::new(19., 9., 16., 8.)));
+        assert_eq!(layout.get(6), None);
+
+        let json = layout.to_json().unwrap();
+        assert_eq!(TextureAtlasLayout::from_json(&json).unwrap(), layout);
+    }
+
+    #[test]
+    fn test_pack_atlas() {
+        let image = |width, height, value| {
+            Image::new(width, height, vec![value; (width * height * 4) as usize])
+        };
+
+        let mut builder = TextureAtlasBuilder::new().max_width(8).padding(1);
+        let small = builder.add_image(image(2, 2, 1));
+        let tall = builder.add_image(image(3, 4, 2));
+        let wide = builder.add_image(image(6, 1, 3));
+
+        let (atlas, layout) = builder.build().unwrap();
+
+        // The tall image starts the first row, the small one fits next to it and the wide one
+        // goes to the next row
+        assert_eq!(layout.get(tall), Some(Rect::new(0., 0., 3., 4.)));
+        assert_eq!(layout.get(small), Some(Rect::new(4., 0., 2., 2.)));
+        assert_eq!(layout.get(wide), Some(Rect::new(0., 5., 6., 1.)));
+        assert_eq!((atlas.width, atlas.height), (6, 6));
+
+        let pixel = |x: u32, y: u32| atlas.data[((y * atlas.width + x) * 4) as usize];
+        assert_eq!(pixel(2, 3), 2);
+        assert_eq!(pixel(5, 1), 1);
+        assert_eq!(pixel(5, 5), 3);
+        assert_eq!(pixel(3, 0), 0);
+
+        let mut too_wide = TextureAtlasBuilder::new().max_width(4);
+        too_wide.add_image(image(5, 1, 0));
+        assert!(too_wide.build().is_err());
+    }
+}
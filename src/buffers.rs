@@ -1,10 +1,12 @@
-use std::{
-    ops::{Range, RangeBounds},
-    usize,
-};
+use std::ops::{Range, RangeBounds};
 
-use wgpu::{BufferSize, BufferSlice, Queue, QueueWriteBufferView};
+use wgpu::{BufferAddress, BufferSize, BufferSlice, Device, Queue, QueueWriteBufferView};
 
+/// A GPU buffer that data is streamed into every frame as consecutive slices
+///
+/// The buffer grows when a frame needs more than its capacity, so [`SlicedBuffer::reserve`] must
+/// be called with the size of the whole frame before pushing, otherwise slices pushed before the
+/// reallocation would be lost
 #[derive(Debug)]
 pub struct SlicedBuffer {
     pub buffer: wgpu::Buffer,
@@ -25,13 +27,105 @@ impl SlicedBuffer {
         self.buffer.slice(range)
     }
 
-    pub fn slices(&self) -> &[Range<usize>] {
-        &self.slices
+    /// Starts a new frame, the next push writes at the start of the buffer
+    pub fn clear(&mut self) {
+        self.slices.clear();
     }
 
-    pub fn write_into<'a>(&'a self, queue: &'a Queue, size: BufferSize) -> QueueWriteBufferView {
+    /// Makes sure `size` bytes fit after the already pushed slices, reallocating the buffer by
+    /// at least doubling its capacity if they don't
+    ///
+    /// The data pushed before a reallocation is lost
+    pub fn reserve(&mut self, device: &Device, size: BufferAddress) {
+        let required = self.end() as BufferAddress + aligned(size);
+        if required <= self.capacity {
+            return;
+        }
+
+        self.capacity = grown_capacity(self.capacity, required);
+        self.buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sliced Buffer"),
+            usage: self.buffer.usage(),
+            size: self.capacity,
+            mapped_at_creation: false,
+        });
+        self.slices.clear();
+    }
+
+    /// Writes `data` after the last slice, returning the index of the new slice
+    ///
+    /// Panics if it doesn't fit, [`SlicedBuffer::reserve`] must be called first
+    pub fn push(&mut self, queue: &Queue, data: &[u8]) -> usize {
+        let start = self.end();
+        let end = start + data.len();
+
+        if let Some(size) = BufferSize::new(aligned(data.len() as BufferAddress)) {
+            assert!(
+                start as BufferAddress + size.get() <= self.capacity,
+                "Pushed {} bytes past the capacity of the buffer",
+                data.len()
+            );
+
+            let mut view = self.write_into(queue, start as BufferAddress, size);
+            if data.len() as BufferAddress == size.get() {
+                view.copy_from_slice(data);
+            } else {
+                let mut padded = data.to_vec();
+                padded.resize(size.get() as usize, 0);
+                view.copy_from_slice(&padded);
+            }
+        }
+
+        self.slices.push(start..end);
+
+        self.slices.len() - 1
+    }
+
+    pub fn write_into<'a>(
+        &'a self,
+        queue: &'a Queue,
+        offset: BufferAddress,
+        size: BufferSize,
+    ) -> QueueWriteBufferView {
         queue
-            .write_buffer_with(&self.buffer, 0, size)
+            .write_buffer_with(&self.buffer, offset, size)
             .expect("Failed to create staging buffer for vertex data")
     }
+
+    /// Where the next slice starts, slices are aligned so they can be written to and bound
+    fn end(&self) -> usize {
+        self.slices
+            .last()
+            .map(|slice| aligned(slice.end as BufferAddress) as usize)
+            .unwrap_or(0)
+    }
+}
+
+fn aligned(size: BufferAddress) -> BufferAddress {
+    size.next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+}
+
+/// Doubles the capacity until `required` fits, so growing is amortized over many frames
+fn grown_capacity(capacity: BufferAddress, required: BufferAddress) -> BufferAddress {
+    let mut capacity = capacity.max(wgpu::COPY_BUFFER_ALIGNMENT);
+    while capacity < required {
+        capacity *= 2;
+    }
+
+    capacity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grown_capacity() {
+        assert_eq!(grown_capacity(1024, 1000), 1024);
+        assert_eq!(grown_capacity(1024, 1025), 2048);
+        assert_eq!(grown_capacity(1024, 5000), 8192);
+        assert_eq!(grown_capacity(0, 6), 8);
+        assert_eq!(aligned(6), 8);
+        assert_eq!(aligned(12), 12);
+    }
 }
@@ -15,6 +15,10 @@ impl CameraUniform {
         }
     }
 
+    pub fn view_proj(&self) -> Mat4 {
+        Mat4::from_cols_array_2d(&self.view_proj)
+    }
+
     pub fn update_view_proj(&mut self, view_projection_matrix: &Mat4) {
         self.view_proj = view_projection_matrix.to_cols_array_2d();
     }
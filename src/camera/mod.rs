@@ -1,10 +1,14 @@
+use std::collections::HashMap;
+
 use camera_uniform::CameraUniform;
+use glam::Vec2;
 use orthographic_camera::OrthographicCamera;
 
 use crate::{
-    ecs::Component,
+    ecs::{Component, rendering::GlobalTransform},
     prelude::{Query, Read, ResMut, Transform},
     renderer::Renderer,
+    window::WindowRef,
 };
 
 pub mod camera_uniform;
@@ -19,29 +23,66 @@ impl Camera {
     pub fn default_2d() -> Self {
         Camera::Ortho(OrthographicCamera::new(0., 0., 0., 0.))
     }
+
+    /// Converts a position in the viewport (e.g. [`Input::cursor_position`](crate::input::Input::cursor_position))
+    /// to world coordinates
+    pub fn viewport_to_world(
+        &self,
+        transform: &Transform,
+        viewport_position: Vec2,
+        viewport_size: Vec2,
+    ) -> Vec2 {
+        match self {
+            Camera::Ortho(orthographic_camera) => {
+                orthographic_camera.viewport_to_world(transform, viewport_position, viewport_size)
+            }
+        }
+    }
 }
 
 impl Component for Camera {}
 
+/// Where a camera renders to, cameras without this component render to the primary window
+///
+/// Cameras rendering to a window have their projection resized with it, cameras rendering to a
+/// texture need [`OrthographicCamera::update_projection_matrix`] to be called with the texture
+/// size
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RenderTarget {
+    Window(WindowRef),
+    /// A view of a texture created with [`wgpu::TextureUsages::RENDER_ATTACHMENT`] and the
+    /// [`Renderer::surface_format`] format
+    Texture(wgpu::TextureView),
+}
+
+impl Default for RenderTarget {
+    fn default() -> Self {
+        RenderTarget::Window(WindowRef::Primary)
+    }
+}
+
+impl Component for RenderTarget {}
+
 pub fn update_camera_uniform(
-    query: Query<(Read<Camera>, Read<Transform>)>,
-    renderer: ResMut<Renderer>,
+    query: Query<(Read<Camera>, Read<GlobalTransform>)>,
+    targets: Query<Read<RenderTarget>>,
+    mut renderer: ResMut<Renderer>,
 ) {
-    // Find the camera entity
-    if let Some((_, (camera, transform))) = query.iter().next() {
+    let targets: HashMap<_, _> = targets.iter().collect();
+
+    renderer.camera_count = 0;
+
+    for (entity, (camera, transform)) in query.iter() {
+        let target = targets.get(&entity).cloned().cloned().unwrap_or_default();
+
         match camera {
             Camera::Ortho(orthographic_camera) => {
                 let view_proj = orthographic_camera.build_view_projection_matrix(transform);
 
-                // Update the camera uniform
                 let mut camera_uniform = CameraUniform::new();
                 camera_uniform.update_view_proj(&view_proj);
 
-                renderer.queue.write_buffer(
-                    &renderer.camera_buffer,
-                    0,
-                    bytemuck::cast_slice(&[camera_uniform]),
-                );
+                renderer.push_camera(target, camera_uniform);
             }
         }
     }
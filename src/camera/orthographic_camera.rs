@@ -1,4 +1,4 @@
-use glam::{Mat4, Vec3};
+use glam::{Mat4, Quat, Vec2, Vec3};
 
 use crate::prelude::Transform;
 
@@ -22,17 +22,132 @@ impl OrthographicCamera {
         self.projection_matrix = Mat4::orthographic_rh(left, right, bottom, top, -1000., 1000.);
     }
 
+    /// The projection of the camera times its view, the inverse of its (global) position and
+    /// rotation
+    ///
+    /// The scale isn't inverted, a camera scale above one zooms in
     pub fn build_view_projection_matrix(&self, transform: &Transform) -> Mat4 {
-        let view = Mat4::look_at_rh(Vec3::new(0., 0., 2.), Vec3::ZERO, Vec3::Y);
+        let camera = Mat4::from_rotation_translation(
+            Quat::from_rotation_z(transform.rotation),
+            transform.position,
+        );
+        let zoom = Mat4::from_scale(transform.scale.max(Vec2::splat(0.001)).extend(1.));
 
-        let scale = Mat4::from_scale(glam::Vec3::new(
-            transform.scale.x.max(0.001),
-            transform.scale.y.max(0.001),
-            1.0,
-        ));
+        self.projection_matrix * zoom * camera.inverse()
+    }
+
+    /// Converts a position in the viewport (in pixels, origin at the top-left corner) to world
+    /// coordinates, e.g. to find what's under the cursor
+    pub fn viewport_to_world(
+        &self,
+        transform: &Transform,
+        viewport_position: Vec2,
+        viewport_size: Vec2,
+    ) -> Vec2 {
+        let ndc = Vec3::new(
+            2. * viewport_position.x / viewport_size.x - 1.,
+            1. - 2. * viewport_position.y / viewport_size.y,
+            0.,
+        );
+
+        self.build_view_projection_matrix(transform)
+            .inverse()
+            .project_point3(ndc)
+            .truncate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use crate::ecs::{
+        default_systems::propagate_transforms,
+        hierarchy::{Children, Parent},
+        rendering::{GlobalTransform, insert_global_transform},
+        scheduler::Scheduler,
+        world::World,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_viewport_to_world() {
+        let camera = OrthographicCamera::new(-400., 400., -300., 300.);
+        let transform = Transform::default();
+        let viewport_size = Vec2::new(800., 600.);
+
+        let center = camera.viewport_to_world(&transform, Vec2::new(400., 300.), viewport_size);
+        assert!(center.abs_diff_eq(Vec2::ZERO, 0.001));
+
+        let top_left = camera.viewport_to_world(&transform, Vec2::ZERO, viewport_size);
+        assert!(top_left.abs_diff_eq(Vec2::new(-400., 300.), 0.001));
+
+        let bottom_right = camera.viewport_to_world(&transform, viewport_size, viewport_size);
+        assert!(bottom_right.abs_diff_eq(Vec2::new(400., -300.), 0.001));
+    }
+
+    #[test]
+    fn test_moved_camera_viewport_to_world() {
+        let camera = OrthographicCamera::new(-400., 400., -300., 300.);
+        let transform = Transform {
+            position: Vec3::new(100., 50., 0.),
+            rotation: FRAC_PI_2,
+            scale: Vec2::splat(2.),
+        };
+        let viewport_size = Vec2::new(800., 600.);
+
+        let center = camera.viewport_to_world(&transform, Vec2::new(400., 300.), viewport_size);
+        assert!(center.abs_diff_eq(Vec2::new(100., 50.), 0.001));
+
+        // The right edge of the viewport is above the camera when it's rotated a quarter turn,
+        // zoomed in twice
+        let right = camera.viewport_to_world(&transform, Vec2::new(800., 300.), viewport_size);
+        assert!(right.abs_diff_eq(Vec2::new(100., 250.), 0.001));
+    }
+
+    #[test]
+    fn test_parented_camera_view() {
+        let mut world = World::new();
+        world.register_component::<Transform>();
+        world.register_component::<GlobalTransform>();
+        world.register_component::<Parent>();
+        world.register_component::<Children>();
+        world
+            .register_component_hooks::<Transform>()
+            .on_add(insert_global_transform);
+
+        let player = world.add_entity(Transform::default());
+        let camera_entity = world.add_entity(Transform {
+            position: Vec3::new(0., 10., 0.),
+            ..Default::default()
+        });
+        world.set_parent(camera_entity, player);
+
+        let mut scheduler = Scheduler::new();
+        scheduler.add_system(propagate_transforms);
+        scheduler.initialize(&mut world);
+
+        world
+            .get_component_mut::<Transform>(player)
+            .unwrap()
+            .position = Vec3::new(50., 0., 0.);
+        scheduler.run(world.as_unsafe_world_cell());
 
-        let view = view * scale;
+        let camera = OrthographicCamera::new(-400., 400., -300., 300.);
+        let global = world
+            .get_component::<GlobalTransform>(camera_entity)
+            .unwrap();
+        let view_proj = camera.build_view_projection_matrix(global);
 
-        self.projection_matrix * view
+        // The point the camera is over ends up in the center of the view
+        let center = view_proj.project_point3(Vec3::new(50., 10., 0.));
+        assert!(center.abs_diff_eq(Vec3::new(0., 0., center.z), 0.001));
+        let origin = view_proj.project_point3(Vec3::ZERO);
+        assert!(
+            origin
+                .truncate()
+                .abs_diff_eq(Vec2::new(-50. / 400., -10. / 300.), 0.001)
+        );
     }
 }
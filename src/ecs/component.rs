@@ -1,11 +1,12 @@
-use std::{any::TypeId, collections::HashMap};
+use std::{alloc::Layout, any::TypeId, borrow::Cow, collections::HashMap, ptr::NonNull};
 
 use super::{
     entity::Entity,
     storage::{
-        blob_vec::BlobVec,
+        blob_vec::{BlobVec, drop_ptr},
         sparse_set::{SparseArray, SparseIndex, SparseSet},
     },
+    world::World,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -52,12 +53,61 @@ impl ComponentSparseSet {
         }
     }
 
-    pub fn insert<T>(&mut self, entity: Entity, value: T) {
+    /// # Safety
+    /// `drop` MUST be safe to call with a pointer to any value inserted into the set
+    pub unsafe fn with_layout(layout: Layout, drop: Option<unsafe fn(*mut u8)>) -> Self {
+        Self {
+            sparse: SparseArray::new(),
+            dense: unsafe { BlobVec::with_layout(layout, drop) },
+            entities: Vec::new(),
+        }
+    }
+
+    /// Inserts the component of the entity, replacing its previous value
+    ///
+    /// Returns whether the component was added, i.e. the entity didn't have it yet
+    pub fn insert<T>(&mut self, entity: Entity, value: T) -> bool {
+        if let Some(&dense_index) = self.sparse.get(entity) {
+            unsafe {
+                *self.dense.get_mut(dense_index).unwrap() = value;
+            }
+            return false;
+        }
+
         self.sparse.insert(entity, self.dense.len());
         self.entities.push(entity);
         unsafe {
             self.dense.push(value);
         }
+
+        true
+    }
+
+    /// Moves the value behind the pointer into the set as the component of the entity, replacing
+    /// its previous value
+    ///
+    /// Returns whether the component was added, i.e. the entity didn't have it yet
+    ///
+    /// # Safety
+    /// `value` MUST point to a valid value of the component, which must not be used or dropped
+    /// afterwards
+    pub unsafe fn insert_raw(&mut self, entity: Entity, value: NonNull<u8>) -> bool {
+        if let Some(&dense_index) = self.sparse.get(entity) {
+            unsafe { self.dense.replace_raw(dense_index, value) };
+            return false;
+        }
+
+        self.sparse.insert(entity, self.dense.len());
+        self.entities.push(entity);
+        unsafe { self.dense.push_raw(value) };
+
+        true
+    }
+
+    /// A pointer to the component of the entity, valid until the set is changed
+    pub fn get_ptr(&self, entity: Entity) -> Option<NonNull<u8>> {
+        let dense_index = self.sparse.get(entity)?;
+        self.dense.get_ptr(*dense_index)
     }
 
     pub fn get<T>(&self, entity: Entity) -> Option<&T> {
@@ -76,15 +126,19 @@ impl ComponentSparseSet {
         unsafe { self.dense.get(dense_index) }
     }
 
-    pub fn remove_entity(&mut self, entity: Entity) {
-        if let Some(dense_index) = self.sparse.remove(entity) {
-            unsafe {
-                self.dense.swap_remove(dense_index);
-            }
-            self.entities.swap_remove(dense_index);
-            let swapped_entity = self.entities[dense_index];
+    /// Removes and drops the component of the entity, returns whether the entity had it
+    pub fn remove_entity(&mut self, entity: Entity) -> bool {
+        let Some(dense_index) = self.sparse.remove(entity) else {
+            return false;
+        };
+
+        self.dense.swap_remove_and_drop(dense_index);
+        self.entities.swap_remove(dense_index);
+        if let Some(&swapped_entity) = self.entities.get(dense_index) {
             self.sparse.insert(swapped_entity, dense_index);
         }
+
+        true
     }
 
     pub fn iter<T>(&self) -> std::slice::Iter<'_, T> {
@@ -95,6 +149,10 @@ impl ComponentSparseSet {
         unsafe { self.dense.iter_mut() }
     }
 
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.sparse.get(entity).is_some()
+    }
+
     /// Returns the how many entities have this component
     pub fn len(&self) -> usize {
         self.entities.len()
@@ -134,17 +192,41 @@ impl Components {
             .insert(component_id, ComponentSparseSet::new::<T>());
     }
 
-    pub fn insert_component<T>(&mut self, entity: Entity, component_id: ComponentId, component: T) {
+    /// Creates the storage of a component from its descriptor
+    pub fn register_descriptor(
+        &mut self,
+        component_id: ComponentId,
+        descriptor: &ComponentDescriptor,
+    ) {
+        let component_sparse_set =
+            unsafe { ComponentSparseSet::with_layout(descriptor.layout, descriptor.drop) };
+        self.components.insert(component_id, component_sparse_set);
+    }
+
+    /// Returns whether the component was added, see [`ComponentSparseSet::insert`]
+    pub fn insert_component<T>(
+        &mut self,
+        entity: Entity,
+        component_id: ComponentId,
+        component: T,
+    ) -> bool {
         self.components
             .get_mut(component_id)
             .unwrap()
-            .insert(entity, component);
+            .insert(entity, component)
+    }
+
+    /// Removes every component of the entity
+    pub fn remove_entity(&mut self, entity: Entity) {
+        for component_sparse_set in self.components.values_mut() {
+            component_sparse_set.remove_entity(entity);
+        }
     }
 
     pub fn has_component(&self, component_id: ComponentId, entity: Entity) -> bool {
         self.components
             .get(component_id)
-            .is_some_and(|c| c.entities.contains(&entity))
+            .is_some_and(|c| c.contains(entity))
     }
 
     pub fn entities(&self, component_id: ComponentId) -> Vec<Entity> {
@@ -155,16 +237,134 @@ impl Components {
     }
 }
 
+/// Runs when a component is added to or removed from an entity
+pub type ComponentHook = fn(&mut World, Entity);
+
+/// The hooks of a component type, registered with [`World::register_component_hooks`]
+///
+/// A component can have several hooks of each kind, they run in the order they were registered
+#[derive(Debug, Clone, Default)]
+pub struct ComponentHooks {
+    on_add: Vec<ComponentHook>,
+    on_insert: Vec<ComponentHook>,
+    on_remove: Vec<ComponentHook>,
+}
+
+impl ComponentHooks {
+    /// Runs when the component is added to an entity that didn't have it, before
+    /// [`ComponentHooks::on_insert`]
+    pub fn on_add(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_add.push(hook);
+        self
+    }
+
+    /// Runs whenever the component is inserted, also when it replaces a previous value
+    pub fn on_insert(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_insert.push(hook);
+        self
+    }
+
+    /// Runs before the component is removed or its entity is despawned, the component can still
+    /// be read
+    pub fn on_remove(&mut self, hook: ComponentHook) -> &mut Self {
+        self.on_remove.push(hook);
+        self
+    }
+
+    pub(crate) fn get_on_add(&self) -> &[ComponentHook] {
+        &self.on_add
+    }
+
+    pub(crate) fn get_on_insert(&self) -> &[ComponentHook] {
+        &self.on_insert
+    }
+
+    pub(crate) fn get_on_remove(&self) -> &[ComponentHook] {
+        &self.on_remove
+    }
+
+    fn is_empty(&self) -> bool {
+        self.on_add.is_empty() && self.on_insert.is_empty() && self.on_remove.is_empty()
+    }
+}
+
+/// The name, layout and drop function of a component, which can also describe components that
+/// only exist at runtime, e.g. ones defined by scripts
+#[derive(Debug, Clone)]
+pub struct ComponentDescriptor {
+    name: Cow<'static, str>,
+    type_id: Option<TypeId>,
+    layout: Layout,
+    drop: Option<unsafe fn(*mut u8)>,
+}
+
+impl ComponentDescriptor {
+    pub fn new<T: 'static>() -> Self {
+        Self {
+            name: Cow::Borrowed(std::any::type_name::<T>()),
+            type_id: Some(TypeId::of::<T>()),
+            layout: Layout::new::<T>(),
+            drop: std::mem::needs_drop::<T>().then_some(drop_ptr::<T> as unsafe fn(*mut u8)),
+        }
+    }
+
+    /// Describes a component without a Rust type, `drop` is called with a pointer to each value
+    /// that is dropped
+    ///
+    /// Values are `layout.size()` bytes, the storage adds the padding that keeps them aligned
+    ///
+    /// # Safety
+    /// `drop` MUST be safe to call with a pointer to any value inserted for the component
+    pub unsafe fn new_with_layout(
+        name: impl Into<Cow<'static, str>>,
+        layout: Layout,
+        drop: Option<unsafe fn(*mut u8)>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            type_id: None,
+            layout,
+            drop,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ComponentInfo {
     id: ComponentId,
-    // TODO: maybe add type name and other stuff
+    name: Cow<'static, str>,
+    type_id: Option<TypeId>,
+    layout: Layout,
+    hooks: ComponentHooks,
 }
 
 impl ComponentInfo {
     pub fn id(&self) -> ComponentId {
         self.id
     }
+
+    /// The full type name, e.g. `dahhan::ecs::rendering::Sprite`, or the name a dynamic
+    /// component was registered with
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The type of the component, `None` for dynamic components
+    pub fn type_id(&self) -> Option<TypeId> {
+        self.type_id
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    pub fn hooks(&self) -> &ComponentHooks {
+        &self.hooks
+    }
+
+    pub fn has_hooks(&self) -> bool {
+        !self.hooks.is_empty()
+    }
 }
 
 #[derive(Debug)]
@@ -182,11 +382,22 @@ impl ComponentsInfo {
     }
 
     pub fn register_component<T: 'static>(&mut self) -> ComponentId {
-        let type_id = TypeId::of::<T>();
+        self.register_descriptor(&ComponentDescriptor::new::<T>())
+    }
+
+    pub fn register_descriptor(&mut self, descriptor: &ComponentDescriptor) -> ComponentId {
         let component_id = ComponentId((self.components.len()) as u32);
-        let component_info = ComponentInfo { id: component_id };
+        let component_info = ComponentInfo {
+            id: component_id,
+            name: descriptor.name.clone(),
+            type_id: descriptor.type_id,
+            layout: descriptor.layout,
+            hooks: ComponentHooks::default(),
+        };
         self.components.push(component_info);
-        self.indices.insert(type_id, component_id);
+        if let Some(type_id) = descriptor.type_id {
+            self.indices.insert(type_id, component_id);
+        }
 
         component_id
     }
@@ -200,16 +411,31 @@ impl ComponentsInfo {
             .get(&type_id)
             .map(|index| self.components[index.sparse_index()].clone())
     }
+
+    pub fn get_by_id(&self, component_id: ComponentId) -> Option<&ComponentInfo> {
+        self.components.get(component_id.sparse_index())
+    }
+
+    pub fn hooks_mut(&mut self, component_id: ComponentId) -> &mut ComponentHooks {
+        &mut self.components[component_id.sparse_index()].hooks
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, ComponentInfo> {
+        self.components.iter()
+    }
 }
 
 pub trait Component: 'static {}
 
 pub trait TupleAddComponent {
+    /// Inserts the components, pushing their ids to `inserted` along with whether each was added
+    /// or replaced a previous value, for the world to run their hooks
     fn add_component(
         self,
         components_info: &ComponentsInfo,
         components: &mut Components,
         entity: Entity,
+        inserted: &mut Vec<(ComponentId, bool)>,
     );
 }
 
@@ -219,10 +445,12 @@ impl TupleAddComponent for () {
         components_info: &ComponentsInfo,
         components: &mut Components,
         entity: Entity,
+        inserted: &mut Vec<(ComponentId, bool)>,
     ) {
         let _ = components_info;
         let _ = components;
         let _ = entity;
+        let _ = inserted;
     }
 }
 
@@ -232,9 +460,11 @@ impl<T: Component> TupleAddComponent for T {
         components_info: &ComponentsInfo,
         components: &mut Components,
         entity: Entity,
+        inserted: &mut Vec<(ComponentId, bool)>,
     ) {
-        let component_info = components_info.get::<T>().unwrap();
-        components.insert_component(entity, component_info.id(), self);
+        let component_id = components_info.get::<T>().unwrap().id();
+        let added = components.insert_component(entity, component_id, self);
+        inserted.push((component_id, added));
     }
 }
 
@@ -244,9 +474,11 @@ impl<T1: Component> TupleAddComponent for (T1,) {
         components_info: &ComponentsInfo,
         components: &mut Components,
         entity: Entity,
+        inserted: &mut Vec<(ComponentId, bool)>,
     ) {
-        let component_info = components_info.get::<T1>().unwrap();
-        components.insert_component(entity, component_info.id(), self);
+        let component_id = components_info.get::<T1>().unwrap().id();
+        let added = components.insert_component(entity, component_id, self.0);
+        inserted.push((component_id, added));
     }
 }
 
@@ -256,9 +488,12 @@ impl<T1: Component, T2: Component> TupleAddComponent for (T1, T2) {
         components_info: &ComponentsInfo,
         components: &mut Components,
         entity: Entity,
+        inserted: &mut Vec<(ComponentId, bool)>,
     ) {
-        self.0.add_component(components_info, components, entity);
-        self.1.add_component(components_info, components, entity);
+        self.0
+            .add_component(components_info, components, entity, inserted);
+        self.1
+            .add_component(components_info, components, entity, inserted);
     }
 }
 
@@ -268,10 +503,14 @@ impl<T1: Component, T2: Component, T3: Component> TupleAddComponent for (T1, T2,
         components_info: &ComponentsInfo,
         components: &mut Components,
         entity: Entity,
+        inserted: &mut Vec<(ComponentId, bool)>,
     ) {
-        self.0.add_component(components_info, components, entity);
-        self.1.add_component(components_info, components, entity);
-        self.2.add_component(components_info, components, entity);
+        self.0
+            .add_component(components_info, components, entity, inserted);
+        self.1
+            .add_component(components_info, components, entity, inserted);
+        self.2
+            .add_component(components_info, components, entity, inserted);
     }
 }
 
@@ -449,4 +688,97 @@ mod tests {
                 .is_empty()
         );
     }
+
+    #[derive(Default)]
+    struct HookCalls(Vec<&'static str>);
+
+    fn push_call(world: &mut World, call: &'static str) {
+        world.write_resource::<HookCalls>().unwrap().0.push(call);
+    }
+
+    #[test]
+    fn test_component_hooks() {
+        let mut world = World::new();
+        world.insert_resource(HookCalls::default());
+        world.register_component::<SomeComponent>();
+        world
+            .register_component_hooks::<SomeComponent>()
+            .on_add(|world, _| push_call(world, "add"))
+            .on_insert(|world, _| push_call(world, "insert"))
+            .on_remove(|world, entity| {
+                assert!(world.get_component::<SomeComponent>(entity).is_some());
+                push_call(world, "remove");
+            });
+
+        let entity = world.add_entity(SomeComponent(1));
+        world.add_component(entity, SomeComponent(2));
+        assert_eq!(world.iter_component::<SomeComponent>().count(), 1);
+        world.remove_component::<SomeComponent>(entity);
+        world.remove_component::<SomeComponent>(entity);
+        world.add_component(entity, SomeComponent(3));
+        world.despawn(entity);
+
+        assert_eq!(
+            world.read_resource::<HookCalls>().unwrap().0,
+            [
+                "add", "insert", "insert", "remove", "add", "insert", "remove"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_component_hooks_replaced_in_tuple() {
+        let mut world = World::new();
+        world.insert_resource(HookCalls::default());
+        world.register_component::<SomeComponent>();
+        world
+            .register_component_hooks::<SomeComponent>()
+            .on_add(|world, _| push_call(world, "add"))
+            .on_insert(|world, _| push_call(world, "insert"));
+
+        let entity = world.add_entity((SomeComponent(1), SomeComponent(2)));
+        assert_eq!(world.get_component::<SomeComponent>(entity).unwrap().0, 2);
+        assert_eq!(
+            world.read_resource::<HookCalls>().unwrap().0,
+            ["add", "insert", "insert"]
+        );
+    }
+
+    #[test]
+    fn test_component_hooks_despawn() {
+        let mut world = World::new();
+        world.insert_resource(HookCalls::default());
+        world.register_component::<SomeComponent>();
+        world.register_component::<SomeOtherComponent>();
+        world
+            .register_component_hooks::<SomeComponent>()
+            .on_remove(|world, entity| {
+                push_call(world, "remove");
+                // Only the first call despawns, the nested despawn runs the hook again
+                if world.read_resource::<HookCalls>().unwrap().0.len() == 1 {
+                    world.despawn(entity);
+                    world.add_entity(SomeOtherComponent(2));
+                }
+            });
+
+        let entity = world.add_entity((SomeComponent(1), SomeOtherComponent(1)));
+        assert!(world.despawn(entity));
+        assert!(!world.contains(entity));
+        assert_eq!(
+            world.read_resource::<HookCalls>().unwrap().0,
+            ["remove", "remove"]
+        );
+
+        // The entity spawned by the hook reuses the slot of the despawned one and keeps its
+        // components
+        let spawned = world.entities()[0];
+        assert!(world.contains(spawned));
+        assert_eq!(
+            world
+                .get_component::<SomeOtherComponent>(spawned)
+                .unwrap()
+                .0,
+            2
+        );
+    }
 }
@@ -1,197 +1,648 @@
+use std::collections::HashMap;
+
+use glam::{Mat2, Vec2};
 use wgpu::Color;
 use winit::dpi::PhysicalSize;
 
-use crate::camera::Camera;
+use crate::animation::{AnimationFinished, SpriteAnimation};
+use crate::atlas::{AtlasSprite, TextureAtlasLayouts};
+use crate::camera::{Camera, RenderTarget};
+use crate::ecs::entity::Entity;
+use crate::ecs::hierarchy::{Children, Parent};
 use crate::ecs::query::{Query, Read};
-use crate::ecs::rendering::{Sprite, Transform};
-use crate::renderer::Renderer;
-use crate::vertices::VertexColored;
-use crate::{WindowResized, window};
+use crate::ecs::rendering::{GlobalTransform, Sprite, Transform};
+use crate::renderer::{Renderer, SpriteBatch};
+use crate::text::{Fonts, Text2d};
+use crate::texture::Images;
+use crate::time::Time;
+use crate::vertices::{QuadVertex, SpriteInstance};
+use crate::window::{CloseRequested, WindowDescriptor, WindowRef};
+use crate::{AppExit, WindowResized};
 
-use super::events::EventReader;
+use super::events::{EventReader, EventWriter};
 use super::query::Write;
-use super::scheduler::ResMut;
+use super::scheduler::{Res, ResMut};
 
 pub(crate) fn resize_surface(
     mut resize_event: EventReader<WindowResized>,
     mut renderer: ResMut<Renderer>,
 ) {
     for new_size in resize_event.read() {
-        renderer.resize(PhysicalSize::new(
-            new_size.width as u32,
-            new_size.height as u32,
-        ));
+        renderer.resize(
+            new_size.window,
+            PhysicalSize::new(new_size.width as u32, new_size.height as u32),
+        );
     }
 }
 
 pub(crate) fn resize_camera(
     mut resize_event: EventReader<WindowResized>,
-    camera: Query<Write<Camera>>,
+    cameras: Query<Write<Camera>>,
+    targets: Query<Read<RenderTarget>>,
 ) {
-    if let Some((_, camera)) = camera.iter().next() {
-        for new_size in resize_event.read() {
-            match camera {
-                Camera::Ortho(orthographic_camera) => {
-                    orthographic_camera.update_projection_matrix(
-                        -new_size.width / 2.,
-                        new_size.width / 2.,
-                        -new_size.height / 2.,
-                        new_size.height / 2.,
-                    );
-                }
+    let targets: HashMap<_, _> = targets.iter().collect();
+
+    // Only the latest size of every window matters
+    let sizes: HashMap<_, _> = resize_event
+        .read()
+        .map(|new_size| (new_size.window, *new_size))
+        .collect();
+
+    if sizes.is_empty() {
+        return;
+    }
+
+    let primary_target = RenderTarget::default();
+
+    for (entity, camera) in cameras.iter() {
+        let target = targets.get(&entity).copied().unwrap_or(&primary_target);
+        let RenderTarget::Window(window) = target else {
+            continue;
+        };
+        let Some(new_size) = sizes.get(window) else {
+            continue;
+        };
+
+        match camera {
+            Camera::Ortho(orthographic_camera) => {
+                orthographic_camera.update_projection_matrix(
+                    -new_size.width / 2.,
+                    new_size.width / 2.,
+                    -new_size.height / 2.,
+                    new_size.height / 2.,
+                );
             }
         }
     }
 }
 
-pub(crate) fn update_window_resource(
-    mut resize_event: EventReader<WindowResized>,
-    mut window: ResMut<window::Window>,
+pub(crate) fn exit_on_close_requested(
+    mut close_requested: EventReader<CloseRequested>,
+    mut app_exit: EventWriter<AppExit>,
+    window_descriptor: Res<WindowDescriptor>,
 ) {
-    for new_size in resize_event.read() {
-        window.width = new_size.width;
-        window.height = new_size.height;
+    if window_descriptor.exit_on_close
+        && close_requested
+            .read()
+            .any(|event| event.window == WindowRef::Primary)
+    {
+        app_exit.send(AppExit);
     }
 }
 
-pub(crate) fn render_sprites(
-    sprites: Query<(Read<Sprite>, Read<Transform>)>,
-    cameras: Query<(Read<Camera>, Read<Transform>)>,
-    mut renderer: ResMut<Renderer>,
+pub(crate) fn animate_sprites(
+    animations: Query<(Write<SpriteAnimation>, Write<AtlasSprite>)>,
+    time: Res<Time>,
+    mut animation_finished: EventWriter<AnimationFinished>,
 ) {
-    if cameras.iter().next().is_some() {
-        let mut vertices = Vec::new();
-        let mut indices = Vec::new();
-        let mut current_index: u16 = 0;
-
-        for (_, (sprite, transform)) in sprites.iter() {
-            let width = sprite.size.x * transform.scale.x;
-            let height = sprite.size.y * transform.scale.y;
-
-            vertices.push(VertexColored {
-                position: [
-                    transform.position.x,
-                    transform.position.y,
-                    transform.position.z,
-                ],
-                color: sprite.color.into(),
-            });
-
-            vertices.push(VertexColored {
-                position: [
-                    transform.position.x,
-                    transform.position.y - height,
-                    transform.position.z,
-                ],
-                color: sprite.color.into(),
-            });
-
-            vertices.push(VertexColored {
-                position: [
-                    transform.position.x + width,
-                    transform.position.y - height,
-                    transform.position.z,
-                ],
-                color: sprite.color.into(),
-            });
-
-            vertices.push(VertexColored {
-                position: [
-                    transform.position.x + width,
-                    transform.position.y,
-                    transform.position.z,
-                ],
-                color: sprite.color.into(),
-            });
-
-            indices.push(current_index);
-            indices.push(current_index + 1);
-            indices.push(current_index + 2);
-
-            indices.push(current_index);
-            indices.push(current_index + 2);
-            indices.push(current_index + 3);
-
-            current_index += 4;
+    for (entity, (animation, atlas_sprite)) in animations.iter() {
+        if animation.advance(time.delta_time()) {
+            animation_finished.send(AnimationFinished { entity });
         }
 
-        renderer.render_sprites(&vertices, &indices);
+        atlas_sprite.index = animation.current_index();
     }
 }
 
-pub(crate) fn draw(mut renderer: ResMut<Renderer>) {
-    let frame = match renderer.surface.get_current_texture() {
-        wgpu::CurrentSurfaceTexture::Success(surface_texture) => surface_texture,
-        wgpu::CurrentSurfaceTexture::Suboptimal(surface_texture) => {
-            drop(surface_texture);
+/// Points the sprites of atlas sprites at their region of the atlas
+pub(crate) fn update_atlas_sprites(
+    sprites: Query<(Read<AtlasSprite>, Write<Sprite>)>,
+    layouts: Res<TextureAtlasLayouts>,
+) {
+    for (_entity, (atlas_sprite, sprite)) in sprites.iter() {
+        sprite.rect = atlas_sprite.rect(&layouts);
+    }
+}
 
-            renderer
-                .surface
-                .configure(&renderer.device, &renderer.config);
+pub(crate) fn prepare_textures(images: Res<Images>, mut renderer: ResMut<Renderer>) {
+    renderer.prepare_textures(&images);
+}
 
-            return;
-        }
-        wgpu::CurrentSurfaceTexture::Timeout | wgpu::CurrentSurfaceTexture::Occluded => {
-            return;
-        }
-        wgpu::CurrentSurfaceTexture::Outdated => {
-            println!("outdated");
-            renderer
-                .surface
-                .configure(&renderer.device, &renderer.config);
-            return;
+/// The UV coordinates of the top-left and bottom-right corners of a sprite
+fn sprite_uvs(sprite: &Sprite, images: &Images) -> (Vec2, Vec2) {
+    let (mut min, mut max) = match (sprite.rect, sprite.texture.and_then(|t| images.get(t))) {
+        (Some(rect), Some(image)) => (rect.min / image.size(), rect.max / image.size()),
+        _ => (Vec2::ZERO, Vec2::ONE),
+    };
+
+    if sprite.flip_x {
+        std::mem::swap(&mut min.x, &mut max.x);
+    }
+
+    if sprite.flip_y {
+        std::mem::swap(&mut min.y, &mut max.y);
+    }
+
+    (min, max)
+}
+
+fn sprite_instance(sprite: &Sprite, transform: &Transform, images: &Images) -> SpriteInstance {
+    let size = sprite.size * transform.scale;
+    let rotation = Mat2::from_angle(transform.rotation);
+    let (uv_min, uv_max) = sprite_uvs(sprite, images);
+
+    SpriteInstance {
+        position: transform.position.into(),
+        axis_x: (rotation.x_axis * size.x).into(),
+        axis_y: (rotation.y_axis * size.y).into(),
+        anchor: sprite.anchor.as_vec().into(),
+        uv_min: uv_min.into(),
+        uv_max: uv_max.into(),
+        color: sprite.color.into(),
+    }
+}
+
+/// Converts the sprites to instances in draw order, grouping consecutive sprites with the same
+/// texture and pipeline into batches
+fn extract_sprite_instances<'a>(
+    sprites: impl IntoIterator<Item = (&'a Sprite, &'a Transform)>,
+    images: &Images,
+) -> (Vec<SpriteInstance>, Vec<SpriteBatch>) {
+    let mut instances = Vec::new();
+    let mut batches: Vec<SpriteBatch> = Vec::new();
+
+    for (sprite, transform) in sprites {
+        let index = instances.len() as u32;
+        instances.push(sprite_instance(sprite, transform, images));
+
+        let translucent = sprite.is_translucent();
+        match batches.last_mut() {
+            Some(batch) if batch.texture == sprite.texture && batch.translucent == translucent => {
+                batch.instances.end = index + 1;
+            }
+            _ => batches.push(SpriteBatch {
+                texture: sprite.texture,
+                translucent,
+                instances: index..index + 1,
+            }),
         }
-        wgpu::CurrentSurfaceTexture::Lost => {
-            println!("lost");
-            renderer.surface = renderer
-                .instance
-                .create_surface(renderer.window.clone())
-                .unwrap();
-            renderer
-                .surface
-                .configure(&renderer.device, &renderer.config);
-            return;
+    }
+
+    (instances, batches)
+}
+
+/// Sorts sprites back to front by `z`
+///
+/// Without a depth buffer everything is drawn in that order, with one opaque sprites are drawn
+/// first and translucent ones blend over them
+fn draw_order<'a>(
+    mut sprites: Vec<(Entity, (&'a Sprite, &'a Transform))>,
+    depth_buffer: bool,
+) -> Vec<(&'a Sprite, &'a Transform)> {
+    // Sorting by entity too keeps the order stable when sprites share a `z`, query order
+    // changes whenever a component is removed
+    sprites.sort_by(|(a_entity, (_, a)), (b_entity, (_, b))| {
+        a.position
+            .z
+            .total_cmp(&b.position.z)
+            .then(a_entity.cmp(b_entity))
+    });
+
+    let sprites = sprites.into_iter().map(|(_, sprite)| sprite);
+
+    if depth_buffer {
+        let (opaque, translucent): (Vec<_>, Vec<_>) =
+            sprites.partition(|(sprite, _)| !sprite.is_translucent());
+        opaque.into_iter().chain(translucent).collect()
+    } else {
+        sprites.collect()
+    }
+}
+
+/// Computes the [`GlobalTransform`] of every entity from its [`Transform`] and those of its
+/// ancestors
+///
+/// Entities whose parent has no [`Transform`] are treated as root entities
+pub(crate) fn propagate_transforms(
+    transforms: Query<(Read<Transform>, Write<GlobalTransform>)>,
+    parents: Query<Read<Parent>>,
+    children: Query<Read<Children>>,
+) {
+    let mut transforms: HashMap<_, _> = transforms.iter().collect();
+    let parents: HashMap<_, _> = parents.iter().collect();
+    let children: HashMap<_, _> = children.iter().collect();
+
+    let roots: Vec<Entity> = transforms
+        .keys()
+        .filter(|entity| {
+            parents
+                .get(entity)
+                .is_none_or(|parent| !transforms.contains_key(&parent.get()))
+        })
+        .copied()
+        .collect();
+
+    for root in roots {
+        let (transform, global_transform) = transforms.get_mut(&root).unwrap();
+        **global_transform = GlobalTransform::from((*transform).clone());
+
+        let mut stack = vec![(root, (**global_transform).clone())];
+        while let Some((entity, parent_transform)) = stack.pop() {
+            for child in children
+                .get(&entity)
+                .into_iter()
+                .flat_map(|children| children.iter())
+            {
+                if let Some((transform, global_transform)) = transforms.get_mut(child) {
+                    **global_transform = parent_transform.mul_transform(transform);
+                    stack.push((*child, (**global_transform).clone()));
+                }
+            }
         }
-        wgpu::CurrentSurfaceTexture::Validation => {
-            unreachable!("validation");
+    }
+}
+
+pub(crate) fn render_sprites(
+    sprites: Query<(Read<Sprite>, Read<GlobalTransform>)>,
+    cameras: Query<(Read<Camera>, Read<GlobalTransform>)>,
+    images: Res<Images>,
+    mut renderer: ResMut<Renderer>,
+) {
+    if cameras.iter().next().is_some() {
+        let sprites = sprites
+            .iter()
+            .map(|(entity, (sprite, transform))| (entity, (sprite, &**transform)))
+            .collect();
+        let sprites = draw_order(sprites, renderer.depth_buffer);
+        let (instances, batches) = extract_sprite_instances(sprites, &images);
+
+        renderer.render_sprites(&instances, batches);
+    }
+}
+
+pub(crate) fn render_text(
+    texts: Query<(Read<Text2d>, Read<GlobalTransform>)>,
+    mut fonts: ResMut<Fonts>,
+    mut renderer: ResMut<Renderer>,
+) {
+    let texts: Vec<_> = texts
+        .iter()
+        .map(|(entity, (text, transform))| (entity, (text, &**transform)))
+        .collect();
+    renderer.prepare_text(&mut fonts, &texts);
+}
+
+pub(crate) fn draw(mut renderer: ResMut<Renderer>) {
+    let windows: Vec<_> = renderer.surfaces.keys().copied().collect();
+    let mut frames = HashMap::new();
+    for window in windows {
+        if let Some(frame) = renderer.acquire_frame(window) {
+            let view = frame
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default());
+            frames.insert(window, (frame, view));
         }
-    };
+    }
+
+    let depth_sizes: Vec<_> = renderer
+        .cameras()
+        .iter()
+        .filter_map(|camera| renderer.target_size(&camera.target))
+        .collect();
+    renderer.prepare_depth_textures(&depth_sizes);
 
-    let view = frame
-        .texture
-        .create_view(&wgpu::TextureViewDescriptor::default());
+    let renderer = &mut *renderer;
 
     let mut encoder = renderer
         .device
         .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
 
-    {
+    // Every target is cleared by the first camera rendering into it
+    let mut cleared: Vec<&RenderTarget> = Vec::new();
+
+    for (index, camera) in renderer.cameras().iter().enumerate() {
+        let view = match &camera.target {
+            RenderTarget::Window(window) => match frames.get(window) {
+                Some((_, view)) => view,
+                None => continue,
+            },
+            RenderTarget::Texture(view) => view,
+        };
+
+        let load = if cleared.contains(&&camera.target) {
+            wgpu::LoadOp::Load
+        } else {
+            cleared.push(&camera.target);
+            wgpu::LoadOp::Clear(Color::BLACK)
+        };
+
         let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: None,
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &view,
+                view,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(Color::BLACK),
+                    load,
                     store: wgpu::StoreOp::Store,
                 },
                 depth_slice: None,
             })],
-            depth_stencil_attachment: None,
+            depth_stencil_attachment: renderer
+                .target_size(&camera.target)
+                .and_then(|size| renderer.depth_textures.get(&size))
+                .map(|view| wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.),
+                        store: wgpu::StoreOp::Discard,
+                    }),
+                    stencil_ops: None,
+                }),
             timestamp_writes: None,
             occlusion_query_set: None,
             multiview_mask: None,
         });
-        rpass.set_pipeline(&renderer.render_pipeline);
-        rpass.set_bind_group(0, &renderer.camera_bind_group, &[]);
-        rpass.set_vertex_buffer(0, renderer.vertex_buffer.get_slice(..));
+        rpass.set_bind_group(0, &camera.bind_group, &[]);
+        rpass.set_vertex_buffer(0, renderer.quad_vertex_buffer.slice(..));
+        rpass.set_vertex_buffer(1, renderer.instance_buffer.get_slice(..));
         rpass.set_index_buffer(
-            renderer.index_buffer.get_slice(..),
+            renderer.quad_index_buffer.slice(..),
             wgpu::IndexFormat::Uint16,
         );
-        rpass.draw_indexed(0..renderer.num_indices, 0, 0..1);
+
+        for batch in &renderer.batches {
+            if batch.translucent {
+                rpass.set_pipeline(&renderer.translucent_pipeline);
+            } else {
+                rpass.set_pipeline(&renderer.render_pipeline);
+            }
+            rpass.set_bind_group(1, renderer.texture_bind_group(batch.texture), &[]);
+            rpass.draw_indexed(
+                0..QuadVertex::INDICES.len() as u32,
+                0,
+                batch.instances.clone(),
+            );
+        }
+
+        if let Some(text) = renderer.text.camera_pass(index) {
+            text.render(&renderer.text.atlas, &mut rpass);
+        }
+    }
+
+    // Screen space text goes over everything, windows without a camera are only cleared
+    for (window, (_, view)) in &frames {
+        let text = renderer.text.screen_pass(*window);
+        let load = if !cleared.contains(&&RenderTarget::Window(*window)) {
+            wgpu::LoadOp::Clear(Color::BLACK)
+        } else if text.is_some() {
+            wgpu::LoadOp::Load
+        } else {
+            continue;
+        };
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            multiview_mask: None,
+        });
+
+        if let Some(text) = text {
+            text.render(&renderer.text.atlas, &mut rpass);
+        }
+    }
+
+    // egui goes over the screen space text of the primary window
+    if let Some((_, view)) = frames.get(&WindowRef::Primary)
+        && let Some(size) = renderer.target_size(&RenderTarget::Window(WindowRef::Primary))
+        && let Some(egui) = renderer.egui.as_mut()
+    {
+        egui.draw(
+            &renderer.device,
+            &renderer.queue,
+            &mut encoder,
+            view,
+            [size.0, size.1],
+        );
     }
 
     renderer.queue.submit(Some(encoder.finish()));
-    renderer.queue.present(frame);
+
+    for (frame, _) in frames.into_values() {
+        renderer.queue.present(frame);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use glam::{Vec3, Vec4};
+
+    use crate::{
+        ecs::rendering::{AlphaMode, Anchor, Rect},
+        texture::Image,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_sprite_uvs() {
+        let mut images = Images::new();
+        let texture = images.add(Image::new(4, 2, vec![0; 4 * 2 * 4]));
+
+        let mut sprite = Sprite {
+            texture: Some(texture),
+            rect: Some(Rect::new(1., 0., 2., 1.)),
+            ..Default::default()
+        };
+        assert_eq!(
+            sprite_uvs(&sprite, &images),
+            (Vec2::new(0.25, 0.), Vec2::new(0.75, 0.5))
+        );
+
+        sprite.flip_x = true;
+        sprite.flip_y = true;
+        assert_eq!(
+            sprite_uvs(&sprite, &images),
+            (Vec2::new(0.75, 0.5), Vec2::new(0.25, 0.))
+        );
+
+        assert_eq!(
+            sprite_uvs(&Sprite::default(), &images),
+            (Vec2::ZERO, Vec2::ONE)
+        );
+    }
+
+    #[test]
+    fn test_sprite_instance_corners() {
+        let images = Images::new();
+        let corner = |sprite: &Sprite, transform: &Transform, corner| {
+            sprite_instance(sprite, transform, &images).corner_position(corner)
+        };
+
+        let mut sprite = Sprite {
+            size: Vec2::new(4., 2.),
+            ..Default::default()
+        };
+        let mut transform = Transform {
+            position: Vec3::new(10., 10., 1.),
+            ..Default::default()
+        };
+
+        // Anchored at the top-left corner by default
+        let top_left = Vec2::new(-0.5, 0.5);
+        let bottom_right = Vec2::new(0.5, -0.5);
+        assert_eq!(
+            corner(&sprite, &transform, top_left),
+            Vec3::new(10., 10., 1.)
+        );
+        assert_eq!(
+            corner(&sprite, &transform, bottom_right),
+            Vec3::new(14., 8., 1.)
+        );
+
+        sprite.anchor = Anchor::Center;
+        transform.rotation = FRAC_PI_2;
+        assert!(
+            corner(&sprite, &transform, bottom_right).abs_diff_eq(Vec3::new(11., 12., 1.), 0.001)
+        );
+    }
+
+    #[test]
+    fn test_extract_sprite_batches() {
+        let mut images = Images::new();
+        let texture = images.add(Image::new(1, 1, vec![255; 4]));
+
+        let transform = Transform::default();
+        let plain = Sprite::default();
+        let textured = Sprite {
+            texture: Some(texture),
+            ..Default::default()
+        };
+        let blended = Sprite {
+            alpha_mode: AlphaMode::Blend,
+            ..textured.clone()
+        };
+        let translucent = Sprite {
+            color: Vec4::new(1., 1., 1., 0.5),
+            ..Default::default()
+        };
+
+        let (instances, batches) = extract_sprite_instances(
+            [
+                (&plain, &transform),
+                (&plain, &transform),
+                (&textured, &transform),
+                (&textured, &transform),
+                (&blended, &transform),
+                (&translucent, &transform),
+            ],
+            &images,
+        );
+
+        assert_eq!(instances.len(), 6);
+        assert_eq!(instances[5].color, [1., 1., 1., 0.5]);
+        assert_eq!(
+            batches,
+            vec![
+                SpriteBatch {
+                    texture: None,
+                    translucent: false,
+                    instances: 0..2,
+                },
+                SpriteBatch {
+                    texture: Some(texture),
+                    translucent: false,
+                    instances: 2..4,
+                },
+                SpriteBatch {
+                    texture: Some(texture),
+                    translucent: true,
+                    instances: 4..5,
+                },
+                SpriteBatch {
+                    texture: None,
+                    translucent: true,
+                    instances: 5..6,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_draw_order() {
+        let mut world = crate::ecs::world::World::new();
+        world.register_component::<Sprite>();
+        world.register_component::<Transform>();
+
+        // The red channel tags the sprites
+        let sprite = |tag, alpha| Sprite {
+            color: Vec4::new(tag, 1., 1., alpha),
+            ..Default::default()
+        };
+        let transform = |z| Transform {
+            position: Vec3::new(0., 0., z),
+            ..Default::default()
+        };
+
+        world.add_entity((sprite(1., 1.), transform(1.)));
+        world.add_entity((sprite(2., 0.5), transform(-1.)));
+        let middle = world.add_entity((sprite(3., 1.), transform(0.)));
+        world.add_entity((sprite(4., 1.), transform(0.)));
+
+        // Swap removing changes the query order, which shouldn't change the draw order
+        world.remove_component::<Sprite>(middle);
+        world.add_component(middle, sprite(3., 1.));
+
+        let mut order = |depth_buffer| {
+            let query = world.query::<(Read<Sprite>, Read<Transform>)>();
+            draw_order(query.iter().collect(), depth_buffer)
+                .into_iter()
+                .map(|(sprite, _)| sprite.color.x)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(order(false), vec![2., 3., 4., 1.]);
+        assert_eq!(order(true), vec![3., 4., 1., 2.]);
+    }
+
+    #[test]
+    fn test_propagate_transforms() {
+        let mut world = crate::ecs::world::World::new();
+        world.register_component::<Transform>();
+        world.register_component::<GlobalTransform>();
+        world.register_component::<Parent>();
+        world.register_component::<Children>();
+        world
+            .register_component_hooks::<Transform>()
+            .on_add(crate::ecs::rendering::insert_global_transform);
+
+        let parent = world.add_entity(Transform {
+            position: Vec3::new(10., 0., 1.),
+            rotation: FRAC_PI_2,
+            scale: Vec2::splat(2.),
+        });
+        let child = world.add_entity(Transform {
+            position: Vec3::new(1., 0., 1.),
+            ..Default::default()
+        });
+        let grandchild = world.add_entity(Transform {
+            position: Vec3::new(0., 1., 0.),
+            ..Default::default()
+        });
+        world.set_parent(child, parent);
+        world.set_parent(grandchild, child);
+
+        let mut scheduler = crate::ecs::scheduler::Scheduler::new();
+        scheduler.add_system(propagate_transforms);
+        scheduler.initialize(&mut world);
+        scheduler.run(world.as_unsafe_world_cell());
+
+        let global = |entity| world.get_component::<GlobalTransform>(entity).unwrap();
+        assert!(global(child).position.abs_diff_eq(Vec3::new(10., 2., 2.), 0.001));
+        assert_eq!(global(child).rotation, FRAC_PI_2);
+        assert_eq!(global(child).scale, Vec2::splat(2.));
+        assert!(global(grandchild).position.abs_diff_eq(Vec3::new(8., 2., 2.), 0.001));
+    }
 }
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 use super::{generational_array::GenerationalIndex, storage::sparse_set::SparseIndex};
 
 // pub struct EntityAllocator(GenerationalIndexAllocator);
@@ -12,7 +14,7 @@ use super::{generational_array::GenerationalIndex, storage::sparse_set::SparseIn
 //     }
 // }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct Entity(GenerationalIndex);
 
 impl Entity {
@@ -27,6 +29,12 @@ impl From<GenerationalIndex> for Entity {
     }
 }
 
+impl From<Entity> for GenerationalIndex {
+    fn from(value: Entity) -> Self {
+        value.0
+    }
+}
+
 impl SparseIndex for Entity {
     fn sparse_index(&self) -> usize {
         self.index()
@@ -10,30 +10,50 @@ use super::{scheduler::SystemParam, world::World};
 
 pub trait Event: Any + Send + Sync + 'static {}
 
+struct RegisteredEvent {
+    name: &'static str,
+    update: Box<dyn Fn(&mut World)>,
+    pending: fn(&World) -> usize,
+}
+
 pub struct EventRegistry {
-    update_functions: Vec<Box<dyn Fn(&mut World)>>,
+    events: Vec<RegisteredEvent>,
 }
 
 impl EventRegistry {
     pub fn new() -> Self {
-        Self {
-            update_functions: Vec::new(),
-        }
+        Self { events: Vec::new() }
     }
 
     pub fn register_event<E: Event>(&mut self) {
-        self.update_functions.push(Box::new(|world: &mut World| {
-            if let Ok(mut events) = world.write_resource::<Events<E>>() {
-                events.update();
-            }
-        }));
+        self.events.push(RegisteredEvent {
+            name: std::any::type_name::<E>(),
+            update: Box::new(|world: &mut World| {
+                if let Ok(mut events) = world.write_resource::<Events<E>>() {
+                    events.update();
+                }
+            }),
+            pending: |world| {
+                world
+                    .read_resource::<Events<E>>()
+                    .map_or(0, |events| events.len())
+            },
+        });
     }
 
     pub fn update_events(&self, world: &mut World) {
-        for event_update in &self.update_functions {
-            event_update(world);
+        for event in &self.events {
+            (event.update)(world);
         }
     }
+
+    /// The type name of every registered event with how many of its events are buffered
+    pub fn pending(&self, world: &World) -> Vec<(&'static str, usize)> {
+        self.events
+            .iter()
+            .map(|event| (event.name, (event.pending)(world)))
+            .collect()
+    }
 }
 
 #[derive(Debug)]
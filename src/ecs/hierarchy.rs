@@ -0,0 +1,182 @@
+use std::ops::Deref;
+
+use super::{
+    Component, entity::Entity, reflect::Reflect, storage::sparse_set::SparseIndex, world::World,
+};
+
+/// The entity this entity is a child of, set with [`World::set_parent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(default)]
+pub struct Parent(Entity);
+
+impl Parent {
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+}
+
+/// Points to no entity, only a placeholder for scenes to fill in
+impl Default for Parent {
+    fn default() -> Self {
+        Self(Entity::new_sparse_index(usize::MAX))
+    }
+}
+
+impl Component for Parent {}
+
+/// The children of an entity in the order they were added, kept in sync with their [`Parent`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Reflect)]
+#[reflect(default)]
+pub struct Children(Vec<Entity>);
+
+impl Deref for Children {
+    type Target = [Entity];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Component for Children {}
+
+impl World {
+    /// Makes `child` a child of `parent`, removing it from its previous parent
+    ///
+    /// # Panics
+    /// Panics if `parent` is `child` or one of its descendants, or if either was despawned
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) {
+        assert!(
+            self.contains(child) && self.contains(parent),
+            "{child:?} and {parent:?} should both be alive to be linked"
+        );
+
+        let mut ancestor = Some(parent);
+        while let Some(entity) = ancestor {
+            assert!(
+                entity != child,
+                "{child:?} can't be a child of its descendant {parent:?}"
+            );
+            ancestor = self.get_component::<Parent>(entity).map(Parent::get);
+        }
+
+        self.remove_parent(child);
+        self.add_component(child, Parent(parent));
+        match self.get_component_mut::<Children>(parent) {
+            Some(children) => children.0.push(child),
+            None => self.add_component(parent, Children(vec![child])),
+        }
+    }
+
+    /// Makes `child` a root entity again, returns its previous parent
+    pub fn remove_parent(&mut self, child: Entity) -> Option<Entity> {
+        let parent = self.get_component::<Parent>(child)?.get();
+        self.remove_component::<Parent>(child);
+
+        if let Some(children) = self.get_component_mut::<Children>(parent) {
+            children.0.retain(|entity| *entity != child);
+            if children.is_empty() {
+                self.remove_component::<Children>(parent);
+            }
+        }
+
+        Some(parent)
+    }
+
+    /// Despawns the entity along with all of its descendants
+    pub fn despawn_recursive(&mut self, entity: Entity) {
+        if let Some(children) = self.get_component::<Children>(entity).cloned() {
+            for child in children.iter() {
+                self.despawn_recursive(*child);
+            }
+        }
+
+        self.despawn(entity);
+    }
+
+    /// Removes the entity from its parent and turns its children into root entities
+    pub(crate) fn detach(&mut self, entity: Entity) {
+        self.remove_parent(entity);
+
+        if let Some(children) = self.get_component::<Children>(entity).cloned() {
+            for child in children.iter() {
+                self.remove_component::<Parent>(*child);
+            }
+            self.remove_component::<Children>(entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_world() -> World {
+        let mut world = World::new();
+        world.register_component::<Parent>();
+        world.register_component::<Children>();
+        world
+    }
+
+    #[test]
+    fn test_reparent() {
+        let mut world = new_world();
+        let a = world.add_entity(());
+        let b = world.add_entity(());
+        let child = world.add_entity(());
+
+        world.set_parent(child, a);
+        assert_eq!(world.get_component::<Parent>(child), Some(&Parent(a)));
+        assert_eq!(&**world.get_component::<Children>(a).unwrap(), [child]);
+
+        world.set_parent(child, b);
+        assert_eq!(world.get_component::<Parent>(child), Some(&Parent(b)));
+        assert_eq!(&**world.get_component::<Children>(b).unwrap(), [child]);
+        assert!(world.get_component::<Children>(a).is_none());
+
+        assert_eq!(world.remove_parent(child), Some(b));
+        assert!(world.get_component::<Parent>(child).is_none());
+        assert!(world.get_component::<Children>(b).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parent_cycle() {
+        let mut world = new_world();
+        let parent = world.add_entity(());
+        let child = world.add_entity(());
+
+        world.set_parent(child, parent);
+        world.set_parent(parent, child);
+    }
+
+    #[test]
+    #[should_panic(expected = "should both be alive to be linked")]
+    fn test_despawned_parent() {
+        let mut world = new_world();
+        let parent = world.add_entity(());
+        let child = world.add_entity(());
+
+        world.despawn(parent);
+        world.set_parent(child, parent);
+    }
+
+    #[test]
+    fn test_despawn() {
+        let mut world = new_world();
+        let root = world.add_entity(());
+        let middle = world.add_entity(());
+        let leaf = world.add_entity(());
+        world.set_parent(middle, root);
+        world.set_parent(leaf, middle);
+
+        assert!(world.despawn(middle));
+        assert!(!world.despawn(middle));
+        assert!(world.get_component::<Children>(root).is_none());
+        assert!(world.get_component::<Parent>(leaf).is_none());
+        assert_eq!(world.entities(), [root, leaf]);
+
+        world.set_parent(leaf, root);
+        world.despawn_recursive(root);
+        assert!(world.entities().is_empty());
+    }
+}
@@ -5,7 +5,11 @@ pub(crate) mod default_systems;
 pub mod entity;
 pub mod events;
 pub mod generational_array;
+pub mod hierarchy;
+pub mod observer;
 pub mod query;
+pub mod reflect;
+pub mod removal;
 pub mod rendering;
 pub mod resources;
 pub mod scheduler;
@@ -0,0 +1,105 @@
+use std::any::{Any, TypeId};
+
+use rustc_hash::FxHashMap;
+
+use super::{entity::Entity, events::Event, world::World};
+
+type ObserverFn = Box<dyn FnMut(&mut World, Entity, &dyn Any)>;
+
+/// The observers of every entity, keyed by the event type they observe
+#[derive(Default)]
+pub struct Observers {
+    observers: FxHashMap<(TypeId, Entity), Vec<ObserverFn>>,
+}
+
+impl Observers {
+    pub(crate) fn remove_entity(&mut self, entity: Entity) {
+        self.observers
+            .retain(|(_, observed), _| *observed != entity);
+    }
+}
+
+impl World {
+    /// Runs `observer` every time an `E` is triggered for the entity with [`World::trigger`]
+    ///
+    /// The observers of an entity are removed when it's despawned
+    pub fn observe<E: Event>(
+        &mut self,
+        entity: Entity,
+        mut observer: impl FnMut(&mut World, Entity, &E) + 'static,
+    ) {
+        self.observers
+            .observers
+            .entry((TypeId::of::<E>(), entity))
+            .or_default()
+            .push(Box::new(move |world, entity, event| {
+                observer(world, entity, event.downcast_ref::<E>().unwrap())
+            }));
+    }
+
+    /// Runs the observers of the entity for `E` right away, returns how many observers ran
+    ///
+    /// Unlike [`EventWriter`](super::events::EventWriter) events, triggered events aren't kept
+    /// around for systems to read
+    pub fn trigger<E: Event>(&mut self, entity: Entity, event: E) -> usize {
+        let key = (TypeId::of::<E>(), entity);
+        let Some(mut observers) = self.observers.observers.remove(&key) else {
+            return 0;
+        };
+
+        for observer in &mut observers {
+            observer(self, entity, &event);
+        }
+        let count = observers.len();
+
+        // The entity may have been despawned by an observer, which also drops its observers
+        if self.contains(entity) {
+            // Observers added while triggering only run on the next trigger
+            let added = self.observers.observers.remove(&key).unwrap_or_default();
+            observers.extend(added);
+            self.observers.observers.insert(key, observers);
+        }
+
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::Component;
+
+    use super::*;
+
+    struct Damage(u32);
+
+    impl Event for Damage {}
+
+    struct Health(u32);
+
+    impl Component for Health {}
+
+    #[test]
+    fn test_observers() {
+        let mut world = World::new();
+        world.register_component::<Health>();
+        let player = world.add_entity(Health(10));
+        let enemy = world.add_entity(Health(10));
+
+        world.observe(player, |world, entity, damage: &Damage| {
+            let health = world.get_component_mut::<Health>(entity).unwrap();
+            health.0 = health.0.saturating_sub(damage.0);
+            if health.0 == 0 {
+                world.despawn(entity);
+            }
+        });
+
+        assert_eq!(world.trigger(player, Damage(4)), 1);
+        assert_eq!(world.trigger(enemy, Damage(4)), 0);
+        assert_eq!(world.get_component::<Health>(player).unwrap().0, 6);
+        assert_eq!(world.get_component::<Health>(enemy).unwrap().0, 10);
+
+        assert_eq!(world.trigger(player, Damage(6)), 1);
+        assert!(!world.contains(player));
+        assert_eq!(world.trigger(player, Damage(1)), 0);
+    }
+}
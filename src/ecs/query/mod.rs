@@ -1,9 +1,10 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ptr::NonNull};
 
 use crate::{World, ecs::world::UnsafeWorldCell};
 
 use super::{
     Component,
+    component::ComponentId,
     entity::Entity,
     scheduler::{Access, SystemParam},
 };
@@ -231,8 +232,98 @@ impl<A: ComponentAccessor, B: ComponentAccessor, C: ComponentAccessor> Component
     }
 }
 
+/// How a [`DynamicQuery`] accesses a component
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DynamicAccess {
+    Read(ComponentId),
+    Write(ComponentId),
+}
+
+impl DynamicAccess {
+    pub fn component_id(&self) -> ComponentId {
+        match self {
+            DynamicAccess::Read(component_id) | DynamicAccess::Write(component_id) => *component_id,
+        }
+    }
+}
+
+/// A query built at runtime from component ids, see [`World::query_dynamic`]
+///
+/// Yields a pointer to every component in the order of the accesses, the components of
+/// [`DynamicAccess::Read`] accesses must not be written through their pointer
+pub struct DynamicQuery<'w> {
+    world: &'w World,
+    accesses: Vec<DynamicAccess>,
+    entities: Vec<Entity>,
+}
+
+impl<'w> DynamicQuery<'w> {
+    pub(crate) fn new(
+        world: &'w mut World,
+        accesses: &[DynamicAccess],
+    ) -> Result<Self, anyhow::Error> {
+        let mut access = Access::new();
+        for dynamic_access in accesses {
+            let component_id = dynamic_access.component_id();
+            if world.components.get(component_id).is_none() {
+                return Err(anyhow::anyhow!("No such component {component_id:?}"));
+            }
+
+            match dynamic_access {
+                DynamicAccess::Read(_) => access.add_read(component_id),
+                DynamicAccess::Write(_) => access.add_write(component_id),
+            }
+        }
+        access
+            .validate()
+            .map_err(|conflict| anyhow::anyhow!(conflict))?;
+
+        let entities = match accesses.first() {
+            Some(first) => world
+                .components
+                .entities(first.component_id())
+                .into_iter()
+                .filter(|entity| {
+                    accesses.iter().all(|dynamic_access| {
+                        world
+                            .components
+                            .has_component(dynamic_access.component_id(), *entity)
+                    })
+                })
+                .collect(),
+            None => world.entities().to_vec(),
+        };
+
+        Ok(Self {
+            world,
+            accesses: accesses.to_vec(),
+            entities,
+        })
+    }
+
+    pub fn iter(self) -> impl Iterator<Item = (Entity, Vec<NonNull<u8>>)> + 'w {
+        let world = self.world;
+        let accesses = self.accesses;
+
+        self.entities.into_iter().filter_map(move |entity| {
+            let components = accesses
+                .iter()
+                .map(|dynamic_access| {
+                    world.get_component_by_id(entity, dynamic_access.component_id())
+                })
+                .collect::<Option<Vec<_>>>()?;
+
+            Some((entity, components))
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::{alloc::Layout, mem::ManuallyDrop};
+
+    use crate::ecs::component::ComponentDescriptor;
+
     use super::*;
 
     #[derive(Debug, PartialEq)]
@@ -493,4 +584,91 @@ mod tests {
 
         let _ = world.query::<(Write<SomeComponent>, Read<SomeComponent>)>();
     }
+
+    unsafe fn drop_string(ptr: *mut u8) {
+        unsafe { ptr.cast::<String>().drop_in_place() }
+    }
+
+    #[test]
+    fn test_dynamic_query() {
+        let mut world = World::new();
+        world.register_component::<SomeComponent>();
+        let descriptor = unsafe {
+            ComponentDescriptor::new_with_layout(
+                "script::Name",
+                Layout::new::<String>(),
+                Some(drop_string),
+            )
+        };
+        let name_id = world.register_dynamic_component(descriptor);
+        let some_id = world.components_info.get::<SomeComponent>().unwrap().id();
+
+        let named = world.add_entity(SomeComponent(1));
+        world.add_entity(SomeComponent(2));
+        for name in ["first", "second"] {
+            let mut name = ManuallyDrop::new(name.to_string());
+            unsafe {
+                world.insert_component_by_id_raw(named, name_id, NonNull::from(&mut *name).cast())
+            };
+        }
+
+        let query = world
+            .query_dynamic(&[DynamicAccess::Write(some_id), DynamicAccess::Read(name_id)])
+            .unwrap();
+        let results: Vec<_> = query.iter().collect();
+        assert_eq!(results.len(), 1);
+        let (entity, components) = &results[0];
+        assert_eq!(*entity, named);
+        unsafe {
+            components[0].cast::<SomeComponent>().as_mut().0 += 10;
+            assert_eq!(components[1].cast::<String>().as_ref(), "second");
+        }
+        assert_eq!(world.get_component::<SomeComponent>(named).unwrap().0, 11);
+        assert_eq!(
+            world.components_info.get_by_id(name_id).unwrap().name(),
+            "script::Name"
+        );
+
+        assert!(
+            world
+                .query_dynamic(&[DynamicAccess::Read(name_id), DynamicAccess::Write(name_id)])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_dynamic_unpadded_component() {
+        let mut world = World::new();
+        let layout = Layout::from_size_align(5, 4).unwrap();
+        let descriptor =
+            unsafe { ComponentDescriptor::new_with_layout("script::Flags", layout, None) };
+        let flags_id = world.register_dynamic_component(descriptor);
+        assert_eq!(
+            world.components_info.get_by_id(flags_id).unwrap().layout(),
+            layout
+        );
+
+        let entities: Vec<_> = (0..3u8)
+            .map(|value| {
+                let entity = world.add_entity(());
+                let mut flags = [value; 5];
+                unsafe {
+                    world.insert_component_by_id_raw(
+                        entity,
+                        flags_id,
+                        NonNull::from(&mut flags).cast(),
+                    )
+                };
+                entity
+            })
+            .collect();
+
+        for (value, entity) in entities.into_iter().enumerate() {
+            let flags = world.get_component_by_id(entity, flags_id).unwrap();
+            assert_eq!(
+                unsafe { flags.cast::<[u8; 5]>().read_unaligned() },
+                [value as u8; 5]
+            );
+        }
+    }
 }
@@ -0,0 +1,587 @@
+use std::{
+    alloc::Layout,
+    any::{Any, TypeId},
+};
+
+use glam::{IVec2, UVec2, Vec2, Vec3, Vec4};
+use rustc_hash::FxHashMap;
+
+pub use dahhan_derive::Reflect;
+
+use super::{entity::Entity, storage::blob_vec::drop_ptr, world::World};
+
+/// Runtime access to the fields of a value, implement it with `#[derive(Reflect)]`
+///
+/// Plain values like numbers and strings have no fields
+pub trait Reflect: Any {
+    /// The names of the fields of the type, empty for plain values
+    fn type_field_names() -> &'static [&'static str]
+    where
+        Self: Sized,
+    {
+        &[]
+    }
+
+    fn field_names(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn field(&self, name: &str) -> Option<&dyn Reflect> {
+        let _ = name;
+        None
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect> {
+        let _ = name;
+        None
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// A default value of the type, types deriving `Reflect` opt in with `#[reflect(default)]`
+    fn reflect_default() -> Option<Box<dyn Reflect>>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// Creates a value from JSON from [`Reflect::to_json`], by default applied to the
+    /// [`Reflect::reflect_default`] value
+    fn from_json(json: &serde_json::Value) -> anyhow::Result<Self>
+    where
+        Self: Sized,
+    {
+        let mut value = Self::reflect_default()
+            .and_then(|value| (value as Box<dyn Any>).downcast::<Self>().ok())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{} can't be created, add #[reflect(default)] to it",
+                    std::any::type_name::<Self>()
+                )
+            })?;
+        value.apply_json(json)?;
+
+        Ok(*value)
+    }
+
+    /// The items of a `Vec` or the value of an `Option`, empty for other values
+    fn items_mut(&mut self) -> Vec<&mut dyn Reflect> {
+        Vec::new()
+    }
+
+    /// Converts the value to JSON, structs become objects of their reflected fields
+    fn to_json(&self) -> serde_json::Value {
+        self.field_names()
+            .iter()
+            .filter_map(|name| Some((name.to_string(), self.field(name)?.to_json())))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
+
+    /// Overwrites the value with JSON from [`Reflect::to_json`], fields missing from the JSON keep
+    /// their value
+    fn apply_json(&mut self, json: &serde_json::Value) -> anyhow::Result<()> {
+        let type_name = self.type_name();
+        let object = json
+            .as_object()
+            .ok_or_else(|| anyhow::anyhow!("Expected an object for {type_name}, found {json}"))?;
+
+        for (name, value) in object {
+            self.field_mut(name)
+                .ok_or_else(|| anyhow::anyhow!("{type_name} has no field {name:?}"))?
+                .apply_json(value)
+                .map_err(|e| anyhow::anyhow!("In field {name:?} of {type_name}: {e}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl dyn Reflect {
+    /// Follows a `.` separated path of field names, the empty path is the value itself
+    pub fn path(&self, path: &str) -> Option<&dyn Reflect> {
+        if path.is_empty() {
+            return Some(self);
+        }
+
+        path.split('.')
+            .try_fold(self, |value, name| value.field(name))
+    }
+
+    pub fn path_mut(&mut self, path: &str) -> Option<&mut dyn Reflect> {
+        if path.is_empty() {
+            return Some(self);
+        }
+
+        path.split('.')
+            .try_fold(self, |value, name| value.field_mut(name))
+    }
+
+    pub fn downcast_ref<T: Reflect>(&self) -> Option<&T> {
+        (self as &dyn Any).downcast_ref()
+    }
+
+    pub fn downcast_mut<T: Reflect>(&mut self) -> Option<&mut T> {
+        (self as &mut dyn Any).downcast_mut()
+    }
+
+    /// Returns the field at `path` (e.g. `"position.x"`) if it has the type `T`
+    pub fn get_path<T: Reflect>(&self, path: &str) -> Option<&T> {
+        self.path(path)?.downcast_ref()
+    }
+
+    /// Replaces the field at `path` (e.g. `"position.x"`)
+    pub fn set_path<T: Reflect>(&mut self, path: &str, value: T) -> anyhow::Result<()> {
+        let type_name = self.type_name();
+        let field = self
+            .path_mut(path)
+            .ok_or_else(|| anyhow::anyhow!("{type_name} has no field {path:?}"))?;
+        let field_type = field.type_name();
+
+        *field.downcast_mut::<T>().ok_or_else(|| {
+            anyhow::anyhow!(
+                "field {path:?} of {type_name} is a {field_type}, not a {}",
+                std::any::type_name::<T>()
+            )
+        })? = value;
+
+        Ok(())
+    }
+}
+
+macro_rules! impl_reflect_value {
+    ($($ty:ty),*) => {
+        $(impl Reflect for $ty {
+            fn reflect_default() -> Option<Box<dyn Reflect>> {
+                Some(Box::new(<$ty>::default()))
+            }
+
+            fn to_json(&self) -> serde_json::Value {
+                serde_json::to_value(self).unwrap_or_default()
+            }
+
+            fn apply_json(&mut self, json: &serde_json::Value) -> anyhow::Result<()> {
+                *self = serde_json::from_value(json.clone()).map_err(|_| {
+                    anyhow::anyhow!("Expected {}, found {json}", stringify!($ty))
+                })?;
+
+                Ok(())
+            }
+        })*
+    };
+}
+
+impl_reflect_value!(
+    bool, f32, f64, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, String
+);
+
+impl Reflect for Entity {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    fn from_json(json: &serde_json::Value) -> anyhow::Result<Self> {
+        serde_json::from_value(json.clone())
+            .map_err(|_| anyhow::anyhow!("Expected an entity, found {json}"))
+    }
+
+    fn apply_json(&mut self, json: &serde_json::Value) -> anyhow::Result<()> {
+        *self = serde_json::from_value(json.clone())
+            .map_err(|_| anyhow::anyhow!("Expected an entity, found {json}"))?;
+
+        Ok(())
+    }
+}
+
+impl<T: Reflect> Reflect for Vec<T> {
+    fn reflect_default() -> Option<Box<dyn Reflect>> {
+        Some(Box::new(Vec::<T>::new()))
+    }
+
+    fn items_mut(&mut self) -> Vec<&mut dyn Reflect> {
+        self.iter_mut()
+            .map(|item| item as &mut dyn Reflect)
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.iter().map(Reflect::to_json).collect()
+    }
+
+    fn apply_json(&mut self, json: &serde_json::Value) -> anyhow::Result<()> {
+        let items = json
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Expected an array, found {json}"))?;
+
+        *self = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                T::from_json(item).map_err(|e| anyhow::anyhow!("In item {index}: {e}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(())
+    }
+}
+
+impl<T: Reflect> Reflect for Option<T> {
+    fn reflect_default() -> Option<Box<dyn Reflect>> {
+        Some(Box::new(None::<T>))
+    }
+
+    fn items_mut(&mut self) -> Vec<&mut dyn Reflect> {
+        self.iter_mut()
+            .map(|item| item as &mut dyn Reflect)
+            .collect()
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.as_ref()
+            .map_or(serde_json::Value::Null, Reflect::to_json)
+    }
+
+    fn apply_json(&mut self, json: &serde_json::Value) -> anyhow::Result<()> {
+        match self {
+            _ if json.is_null() => *self = None,
+            Some(value) => value.apply_json(json)?,
+            None => *self = Some(T::from_json(json)?),
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! impl_reflect_struct {
+    ($ty:ty { $($field:ident),* }) => {
+        impl Reflect for $ty {
+            fn reflect_default() -> Option<Box<dyn Reflect>> {
+                Some(Box::new(<$ty>::default()))
+            }
+
+            fn type_field_names() -> &'static [&'static str] {
+                &[$(stringify!($field)),*]
+            }
+
+            fn field_names(&self) -> &'static [&'static str] {
+                &[$(stringify!($field)),*]
+            }
+
+            fn field(&self, name: &str) -> Option<&dyn Reflect> {
+                match name {
+                    $(stringify!($field) => Some(&self.$field),)*
+                    _ => None,
+                }
+            }
+
+            fn field_mut(&mut self, name: &str) -> Option<&mut dyn Reflect> {
+                match name {
+                    $(stringify!($field) => Some(&mut self.$field),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_reflect_struct!(Vec2 { x, y });
+impl_reflect_struct!(Vec3 { x, y, z });
+impl_reflect_struct!(Vec4 { x, y, z, w });
+impl_reflect_struct!(UVec2 { x, y });
+impl_reflect_struct!(IVec2 { x, y });
+
+/// What the [`TypeRegistry`] knows about a component or resource type
+#[derive(Debug, Clone)]
+pub struct TypeRegistration {
+    type_id: TypeId,
+    name: &'static str,
+    layout: Layout,
+    drop: Option<unsafe fn(*mut u8)>,
+    reflect: Option<ReflectData>,
+}
+
+impl TypeRegistration {
+    pub fn of<T: 'static>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            name: std::any::type_name::<T>(),
+            layout: Layout::new::<T>(),
+            drop: std::mem::needs_drop::<T>().then_some(drop_ptr::<T> as unsafe fn(*mut u8)),
+            reflect: None,
+        }
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// The full type name, e.g. `dahhan::ecs::rendering::Sprite`
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Drops the value behind the pointer in place, `None` if the type doesn't need dropping
+    pub fn drop_fn(&self) -> Option<unsafe fn(*mut u8)> {
+        self.drop
+    }
+
+    /// Only set for types registered with [`TypeRegistry::register_reflect`]
+    pub fn reflect(&self) -> Option<&ReflectData> {
+        self.reflect.as_ref()
+    }
+}
+
+type ResourceMutFn = fn(&World, &mut dyn FnMut(&mut dyn Reflect)) -> bool;
+type InsertComponentFn = fn(&mut World, Entity, Box<dyn Reflect>) -> anyhow::Result<()>;
+
+/// Reflection of a registered type, lets tooling reach the components and resources of that type
+/// in a [`World`] without knowing the type statically
+#[derive(Debug, Clone)]
+pub struct ReflectData {
+    field_names: &'static [&'static str],
+    component: fn(&World, Entity) -> Option<&dyn Reflect>,
+    component_mut: fn(&mut World, Entity) -> Option<&mut dyn Reflect>,
+    resource_mut: ResourceMutFn,
+    default: fn() -> Option<Box<dyn Reflect>>,
+    insert_component: InsertComponentFn,
+    insert_resource: fn(&mut World, Box<dyn Reflect>),
+}
+
+impl ReflectData {
+    pub fn of<T: Reflect>() -> Self {
+        Self {
+            field_names: T::type_field_names(),
+            component: |world, entity| {
+                let info = world.components_info.get::<T>()?;
+                let component = world.components.get(info.id())?.get::<T>(entity)?;
+                Some(component)
+            },
+            component_mut: |world, entity| {
+                let info = world.components_info.get::<T>()?;
+                let component = world.components.get_mut(info.id())?.get_mut::<T>(entity)?;
+                Some(component)
+            },
+            resource_mut: |world, f| match world.write_resource::<T>() {
+                Ok(mut resource) => {
+                    f(&mut *resource);
+                    true
+                }
+                Err(_) => false,
+            },
+            default: T::reflect_default,
+            insert_component: |world, entity, value| {
+                let value = downcast_box::<T>(value);
+                let info = world.components_info.get::<T>().ok_or_else(|| {
+                    anyhow::anyhow!("{} is not a component", std::any::type_name::<T>())
+                })?;
+                world.insert_component_by_id(entity, info.id(), value);
+
+                Ok(())
+            },
+            insert_resource: |world, value| world.insert_resource(downcast_box::<T>(value)),
+        }
+    }
+
+    pub fn field_names(&self) -> &'static [&'static str] {
+        self.field_names
+    }
+
+    /// The component of this type on `entity`
+    pub fn component<'w>(&self, world: &'w World, entity: Entity) -> Option<&'w dyn Reflect> {
+        (self.component)(world, entity)
+    }
+
+    pub fn component_mut<'w>(
+        &self,
+        world: &'w mut World,
+        entity: Entity,
+    ) -> Option<&'w mut dyn Reflect> {
+        (self.component_mut)(world, entity)
+    }
+
+    /// Calls `f` with the resource of this type, returns `false` if there is no such resource
+    pub fn resource_mut(&self, world: &World, mut f: impl FnMut(&mut dyn Reflect)) -> bool {
+        (self.resource_mut)(world, &mut f)
+    }
+
+    /// A new default value, `None` unless the type opted in with `#[reflect(default)]`
+    pub fn default_value(&self) -> Option<Box<dyn Reflect>> {
+        (self.default)()
+    }
+
+    /// Adds a component to `entity`, fails if the type isn't a registered component
+    ///
+    /// # Panics
+    /// Panics if `value` isn't of this type
+    pub fn insert_component(
+        &self,
+        world: &mut World,
+        entity: Entity,
+        value: Box<dyn Reflect>,
+    ) -> anyhow::Result<()> {
+        (self.insert_component)(world, entity, value)
+    }
+
+    /// Inserts or replaces the resource of this type
+    ///
+    /// # Panics
+    /// Panics if `value` isn't of this type
+    pub fn insert_resource(&self, world: &mut World, value: Box<dyn Reflect>) {
+        (self.insert_resource)(world, value)
+    }
+}
+
+fn downcast_box<T: Reflect>(value: Box<dyn Reflect>) -> T {
+    let value: Box<dyn Any> = value;
+    *value
+        .downcast::<T>()
+        .unwrap_or_else(|_| panic!("reflected value isn't a {}", std::any::type_name::<T>()))
+}
+
+/// Every component and resource type of a [`World`], by [`TypeId`] and by name
+#[derive(Debug, Default)]
+pub struct TypeRegistry {
+    registrations: FxHashMap<TypeId, TypeRegistration>,
+    names: FxHashMap<&'static str, TypeId>,
+}
+
+impl TypeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the type without reflection, does nothing if it is already registered
+    pub fn register<T: 'static>(&mut self) -> &mut TypeRegistration {
+        self.names
+            .insert(std::any::type_name::<T>(), TypeId::of::<T>());
+        self.registrations
+            .entry(TypeId::of::<T>())
+            .or_insert_with(TypeRegistration::of::<T>)
+    }
+
+    /// Registers the type with its [`ReflectData`]
+    pub fn register_reflect<T: Reflect>(&mut self) {
+        self.register::<T>().reflect = Some(ReflectData::of::<T>());
+    }
+
+    pub fn get(&self, type_id: TypeId) -> Option<&TypeRegistration> {
+        self.registrations.get(&type_id)
+    }
+
+    /// Looks up a type by its full name, e.g. `dahhan::ecs::rendering::Sprite`
+    pub fn get_by_name(&self, name: &str) -> Option<&TypeRegistration> {
+        self.get(*self.names.get(name)?)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TypeRegistration> {
+        self.registrations.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::{Component, rendering::Transform};
+
+    use super::*;
+
+    #[derive(Reflect, Default)]
+    struct Health(u32);
+
+    #[derive(Reflect, Default)]
+    struct Player {
+        name: String,
+        velocity: Vec2,
+        health: Health,
+        #[reflect(ignore)]
+        #[allow(unused)]
+        secret: Vec<u8>,
+    }
+
+    impl Component for Player {}
+
+    #[test]
+    fn test_reflect_paths() {
+        let mut player = Player {
+            name: "Ali".to_string(),
+            ..Default::default()
+        };
+        let player: &mut dyn Reflect = &mut player;
+
+        assert_eq!(player.field_names(), ["name", "velocity", "health"]);
+        assert_eq!(player.get_path::<String>("name").unwrap(), "Ali");
+        assert!(player.path("secret").is_none());
+
+        player.set_path("velocity.y", 2.5_f32).unwrap();
+        player.set_path("health.0", 10_u32).unwrap();
+        assert_eq!(*player.get_path::<f32>("velocity.y").unwrap(), 2.5);
+        assert_eq!(*player.get_path::<u32>("health.0").unwrap(), 10);
+
+        assert!(player.set_path("velocity.w", 1_f32).is_err());
+        assert!(player.set_path("velocity.x", 1_u32).is_err());
+    }
+
+    #[test]
+    fn test_reflect_vec_and_option() {
+        let mut path: Vec<Vec2> = vec![Vec2::ONE];
+        let json = serde_json::json!([{ "x": 1., "y": 2. }, { "x": 3. }]);
+        path.apply_json(&json).unwrap();
+        assert_eq!(path, [Vec2::new(1., 2.), Vec2::new(3., 0.)]);
+        assert_eq!(
+            path.to_json(),
+            serde_json::json!([{ "x": 1., "y": 2. }, { "x": 3., "y": 0. }])
+        );
+        assert_eq!(path.items_mut().len(), 2);
+
+        let mut target: Option<Entity> = None;
+        let entity = serde_json::json!({ "index": 4, "generation": 1 });
+        target.apply_json(&entity).unwrap();
+        assert_eq!(target.to_json(), entity);
+        target.apply_json(&serde_json::Value::Null).unwrap();
+        assert!(target.is_none());
+
+        let error = Vec::<u32>::from_json(&serde_json::json!([1, "two"])).unwrap_err();
+        assert_eq!(error.to_string(), "In item 1: Expected u32, found \"two\"");
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut world = World::new();
+        world.register_component::<Player>();
+        world.register_type::<Player>();
+        let entity = world.add_entity(Player::default());
+
+        let registry = world.type_registry();
+        let registration = registry
+            .get_by_name(std::any::type_name::<Player>())
+            .unwrap();
+        assert_eq!(registration.layout(), Layout::new::<Player>());
+        assert!(registration.drop_fn().is_some());
+        assert!(TypeRegistration::of::<Transform>().drop_fn().is_none());
+
+        let reflect = registration.reflect().unwrap().clone();
+        reflect
+            .component_mut(&mut world, entity)
+            .unwrap()
+            .set_path("velocity.x", 3_f32)
+            .unwrap();
+
+        assert_eq!(
+            world.get_component::<Player>(entity).unwrap().velocity.x,
+            3.
+        );
+        assert_eq!(
+            reflect
+                .component(&world, entity)
+                .unwrap()
+                .get_path::<f32>("velocity.x"),
+            Some(&3.)
+        );
+    }
+}
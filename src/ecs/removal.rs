@@ -0,0 +1,142 @@
+use std::marker::PhantomData;
+
+use rustc_hash::FxHashMap;
+
+use super::{
+    Component,
+    component::ComponentId,
+    entity::Entity,
+    events::{Event, EventIterator, Events},
+    scheduler::{Access, SystemParam},
+    world::{UnsafeWorldCell, World},
+};
+
+/// An entity that lost a component, either removed or despawned
+#[derive(Debug, Clone, Copy)]
+struct RemovedComponentEntity(Entity);
+
+impl Event for RemovedComponentEntity {}
+
+/// The entities that lost each component type, buffered for two frames like [`Events`]
+#[derive(Default)]
+pub(crate) struct RemovedComponentEvents {
+    events: FxHashMap<ComponentId, Events<RemovedComponentEntity>>,
+}
+
+impl RemovedComponentEvents {
+    pub(crate) fn send(&mut self, component_id: ComponentId, entity: Entity) {
+        self.events
+            .entry(component_id)
+            .or_insert_with(Events::new)
+            .send(RemovedComponentEntity(entity));
+    }
+
+    pub(crate) fn update(&mut self) {
+        for events in self.events.values_mut() {
+            events.update();
+        }
+    }
+}
+
+/// The entities whose `T` was removed or that were despawned since the system last ran
+///
+/// Like events, removals are kept for two frames, so a system has to run at least every other
+/// frame to see all of them
+pub struct RemovedComponents<'s, 'w, T: Component> {
+    cursor: &'s mut usize,
+    events: Option<&'w Events<RemovedComponentEntity>>,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Component> RemovedComponents<'_, '_, T> {
+    /// The entities that lost `T` since the last read, oldest first
+    pub fn read(&mut self) -> impl Iterator<Item = Entity> + '_ {
+        self.events
+            .map(|events| EventIterator::new(self.cursor, events))
+            .into_iter()
+            .flatten()
+            .map(|removed| removed.0)
+    }
+}
+
+impl<T: Component> SystemParam for RemovedComponents<'_, '_, T> {
+    type State = (ComponentId, usize);
+
+    type Item<'world, 'state> = RemovedComponents<'state, 'world, T>;
+
+    fn init_state(world: &mut World) -> Self::State {
+        let component_info = world
+            .components_info
+            .get::<T>()
+            .expect("component should be registered before reading its removals");
+
+        (component_info.id(), 0)
+    }
+
+    fn init_access(_world: &mut World, _access: &mut Access) {}
+
+    unsafe fn get_param<'w, 's>(
+        world: UnsafeWorldCell<'w>,
+        state: &'s mut Self::State,
+    ) -> Self::Item<'w, 's> {
+        let (component_id, cursor) = state;
+        let events = unsafe { world.removed_components() }
+            .events
+            .get(component_id);
+
+        RemovedComponents {
+            cursor,
+            events,
+            phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ecs::{events::EventRegistry, scheduler::ResMut, scheduler::Scheduler};
+
+    use super::*;
+
+    #[allow(unused)]
+    struct Body(u32);
+
+    impl Component for Body {}
+
+    #[derive(Default)]
+    struct Seen(Vec<Entity>);
+
+    fn record_removed(mut bodies: RemovedComponents<Body>, mut seen: ResMut<Seen>) {
+        seen.0.extend(bodies.read());
+    }
+
+    #[test]
+    fn test_removed_components() {
+        let mut world = World::new();
+        world.insert_resource(EventRegistry::new());
+        world.insert_resource(Seen::default());
+        world.register_component::<Body>();
+
+        let mut scheduler = Scheduler::new();
+        scheduler.add_system(record_removed);
+        scheduler.initialize(&mut world);
+
+        let a = world.add_entity(Body(0));
+        let b = world.add_entity(Body(0));
+        let c = world.add_entity(Body(0));
+        world.remove_component::<Body>(a);
+        world.despawn(c);
+        scheduler.run(world.as_unsafe_world_cell());
+        assert_eq!(world.read_resource::<Seen>().unwrap().0, [a, c]);
+
+        world.update_events();
+        world.despawn(b);
+        scheduler.run(world.as_unsafe_world_cell());
+        assert_eq!(world.read_resource::<Seen>().unwrap().0, [a, c, b]);
+
+        world.update_events();
+        world.update_events();
+        scheduler.run(world.as_unsafe_world_cell());
+        assert_eq!(world.read_resource::<Seen>().unwrap().0.len(), 3);
+    }
+}
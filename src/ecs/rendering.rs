@@ -1,18 +1,123 @@
-use crate::ecs::Component;
-use glam::{Vec2, Vec3};
+use crate::{
+    ecs::{Component, entity::Entity, reflect::Reflect, world::World},
+    texture::TextureHandle,
+};
+use std::ops::Deref;
 
-#[derive(Debug, Clone)]
+use glam::{Mat2, Vec2, Vec3, Vec4};
+
+/// An axis aligned rectangle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            min: Vec2::new(x, y),
+            max: Vec2::new(x + width, y + height),
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+}
+
+/// The point of a sprite placed at the [`Transform`] position, the sprite rotates around it
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Anchor {
+    Center,
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    /// Relative to the sprite size, `(-0.5, -0.5)` is the bottom-left corner and `(0.5, 0.5)` the
+    /// top-right corner
+    Custom(Vec2),
+}
+
+impl Anchor {
+    pub fn as_vec(&self) -> Vec2 {
+        match self {
+            Anchor::Center => Vec2::ZERO,
+            Anchor::TopLeft => Vec2::new(-0.5, 0.5),
+            Anchor::TopRight => Vec2::new(0.5, 0.5),
+            Anchor::BottomLeft => Vec2::new(-0.5, -0.5),
+            Anchor::BottomRight => Vec2::new(0.5, -0.5),
+            Anchor::Custom(anchor) => *anchor,
+        }
+    }
+}
+
+/// How the alpha of a sprite is used when a depth buffer is enabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+    /// Drawn before the translucent sprites and hides what's behind it, unless the color alpha is
+    /// below one
+    #[default]
+    Opaque,
+    /// Blended over the opaque sprites back to front, e.g. for textures with transparent pixels
+    Blend,
+}
+
+#[derive(Debug, Clone, Reflect)]
+#[reflect(default)]
 pub struct Sprite {
-    pub texture_id: Option<wgpu::Texture>,
+    /// Drawn as a plain colored quad if `None`
+    #[reflect(ignore)]
+    pub texture: Option<TextureHandle>,
     pub size: Vec2,
-    pub color: Vec3,
+    /// RGBA tint multiplied with the texture color, the alpha isn't premultiplied
+    pub color: Vec4,
+    #[reflect(ignore)]
+    pub anchor: Anchor,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Region of the texture to draw in pixels, the top-left corner is the origin. The whole
+    /// texture is drawn if `None`
+    #[reflect(ignore)]
+    pub rect: Option<Rect>,
+    #[reflect(ignore)]
+    pub alpha_mode: AlphaMode,
+}
+
+impl Default for Sprite {
+    fn default() -> Self {
+        Self {
+            texture: None,
+            size: Vec2::ONE,
+            color: Vec4::ONE,
+            anchor: Anchor::TopLeft,
+            flip_x: false,
+            flip_y: false,
+            rect: None,
+            alpha_mode: AlphaMode::Opaque,
+        }
+    }
+}
+
+impl Sprite {
+    /// Sprites that aren't opaque are blended and drawn back to front after the opaque ones
+    ///
+    /// The pixels of the texture aren't checked, textures with transparent pixels need
+    /// [`AlphaMode::Blend`]
+    pub fn is_translucent(&self) -> bool {
+        self.color.w < 1. || self.alpha_mode == AlphaMode::Blend
+    }
 }
 
 impl Component for Sprite {}
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[reflect(default)]
 pub struct Transform {
+    /// Higher `z` values are drawn in front
     pub position: Vec3,
+    /// Counter-clockwise rotation in radians around the sprite [`Anchor`]
     pub rotation: f32,
     pub scale: Vec2,
 }
@@ -28,3 +133,56 @@ impl Default for Transform {
         }
     }
 }
+
+/// The [`Transform`] of an entity in world space, computed from its own transform and those of
+/// its ancestors (see [`Parent`](super::hierarchy::Parent)) every frame before rendering
+///
+/// Added along with a [`Transform`]. The scale of a parent is applied along its own axes, so a
+/// rotated parent with a non-uniform scale stretches its children along their axes instead of
+/// skewing them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GlobalTransform(Transform);
+
+impl GlobalTransform {
+    /// The global transform of a child with the `local` transform
+    pub fn mul_transform(&self, local: &Transform) -> Self {
+        let parent = &self.0;
+        let offset = Mat2::from_angle(parent.rotation) * (parent.scale * local.position.truncate());
+
+        Self(Transform {
+            position: (parent.position.truncate() + offset)
+                .extend(parent.position.z + local.position.z),
+            rotation: parent.rotation + local.rotation,
+            scale: parent.scale * local.scale,
+        })
+    }
+}
+
+impl From<Transform> for GlobalTransform {
+    fn from(transform: Transform) -> Self {
+        Self(transform)
+    }
+}
+
+impl Deref for GlobalTransform {
+    type Target = Transform;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Component for GlobalTransform {}
+
+/// The `on_add` hook of [`Transform`], gives the entity a [`GlobalTransform`] if it doesn't have
+/// one yet
+pub(crate) fn insert_global_transform(world: &mut World, entity: Entity) {
+    if world.get_component::<GlobalTransform>(entity).is_some() {
+        return;
+    }
+
+    if let Some(transform) = world.get_component::<Transform>(entity) {
+        let global_transform = GlobalTransform::from(transform.clone());
+        world.add_component(entity, global_transform);
+    }
+}
@@ -1,7 +1,10 @@
 use std::{
+    any::TypeId,
+    cell::{Ref, RefMut},
     marker::PhantomData,
     ops::{Deref, DerefMut},
     sync::{RwLockReadGuard, RwLockWriteGuard},
+    time::{Duration, Instant},
 };
 
 use crate::{
@@ -31,6 +34,16 @@ pub trait SystemParam {
 pub trait System {
     unsafe fn run_unsafe(&mut self, world: UnsafeWorldCell<'_>);
     fn initialize(&mut self, world: &mut World);
+
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Whether the system can run on any thread, systems using [`NonSend`] or [`NonSendMut`]
+    /// always have to run on the main thread
+    fn is_send(&self) -> bool {
+        true
+    }
 }
 
 pub trait SystemParamFunction<Marker>: Send + Sync + 'static {
@@ -177,6 +190,8 @@ impl<T1: SystemParam, T2: SystemParam, T3: SystemParam, T4: SystemParam> SystemP
 pub struct Scheduler {
     startup_systems: Vec<StoredSystem>,
     systems: Vec<StoredSystem>,
+    /// How long each system in `systems` took in the last run
+    timings: Vec<Duration>,
 }
 
 impl Scheduler {
@@ -184,6 +199,7 @@ impl Scheduler {
         Self {
             systems: Vec::new(),
             startup_systems: Vec::new(),
+            timings: Vec::new(),
         }
     }
 
@@ -196,13 +212,33 @@ impl Scheduler {
     }
 
     pub fn run(&mut self, world: UnsafeWorldCell<'_>) {
-        for system in &mut self.systems {
+        self.timings.resize(self.systems.len(), Duration::ZERO);
+
+        for (system, timing) in self.systems.iter_mut().zip(&mut self.timings) {
+            // Systems run one after the other on the calling thread, a parallel executor has to
+            // keep the ones that aren't `Send` on the main thread too
+            assert!(
+                system.is_send() || world.is_main_thread(),
+                "{} uses non-send resources and has to run on the main thread",
+                system.name()
+            );
+
+            let start = Instant::now();
             unsafe {
                 system.run_unsafe(world);
             }
+            *timing = start.elapsed();
         }
     }
 
+    /// The name of every system in run order with how long it took in the last run
+    pub fn systems(&self) -> impl Iterator<Item = (&'static str, Duration)> + '_ {
+        self.systems.iter().enumerate().map(|(index, system)| {
+            let timing = self.timings.get(index).copied().unwrap_or_default();
+            (system.name(), timing)
+        })
+    }
+
     pub fn add_startup_system<O, M, S: System + 'static>(
         &mut self,
         system: impl IntoSystem<O, M, System = S>,
@@ -231,6 +267,8 @@ impl Scheduler {
 pub struct Access {
     reads: Vec<ComponentId>,
     writes: Vec<ComponentId>,
+    non_send_reads: Vec<(TypeId, &'static str)>,
+    non_send_writes: Vec<(TypeId, &'static str)>,
 }
 
 impl Access {
@@ -238,9 +276,26 @@ impl Access {
         Self {
             reads: Vec::new(),
             writes: Vec::new(),
+            non_send_reads: Vec::new(),
+            non_send_writes: Vec::new(),
         }
     }
 
+    /// Whether the access needs the main thread, i.e. it uses non-send resources
+    pub fn is_non_send(&self) -> bool {
+        !self.non_send_reads.is_empty() || !self.non_send_writes.is_empty()
+    }
+
+    pub fn add_non_send_read<T: 'static>(&mut self) {
+        self.non_send_reads
+            .push((TypeId::of::<T>(), std::any::type_name::<T>()));
+    }
+
+    pub fn add_non_send_write<T: 'static>(&mut self) {
+        self.non_send_writes
+            .push((TypeId::of::<T>(), std::any::type_name::<T>()));
+    }
+
     pub fn add_write(&mut self, component_id: ComponentId) {
         self.writes.push(component_id);
     }
@@ -252,6 +307,8 @@ impl Access {
     pub fn extend(&mut self, other: Self) {
         self.reads.extend(other.reads);
         self.writes.extend(other.writes);
+        self.non_send_reads.extend(other.non_send_reads);
+        self.non_send_writes.extend(other.non_send_writes);
     }
 
     pub fn validate(&self) -> Result<(), String> {
@@ -273,6 +330,28 @@ impl Access {
             ));
         }
 
+        for (write_type, name) in &self.non_send_writes {
+            if self
+                .non_send_reads
+                .iter()
+                .any(|(read_type, _)| read_type == write_type)
+            {
+                return Err(format!(
+                    "uh oh, writing and reading non-send resource: {name}",
+                ));
+            }
+        }
+
+        let mut non_send_writes = self.non_send_writes.clone();
+        non_send_writes.sort_unstable_by_key(|(type_id, _)| *type_id);
+
+        if let Some(dupe) = non_send_writes.windows(2).find(|w| w[0].0 == w[1].0) {
+            return Err(format!(
+                "uh oh duplicate write on same non-send resource: {}",
+                dupe[0].1
+            ));
+        }
+
         Ok(())
     }
 }
@@ -289,6 +368,7 @@ where
     // TODO: add state to systems so we can have resources local to the system
     // (for example an `EventReader` that tracks which events were read by the system)
     state: Option<FunctionSystemState<F::Param>>,
+    is_send: bool,
 
     // we need a marker because otherwise we're not using `Input`.
     // fn() -> Input is chosen because just using Input would not be `Send` + `Sync`,
@@ -313,6 +393,14 @@ impl<Marker: 'static, F: SystemParamFunction<Marker>> System for FunctionSystem<
         self.f.run(param_state);
     }
 
+    fn name(&self) -> &'static str {
+        std::any::type_name::<F>()
+    }
+
+    fn is_send(&self) -> bool {
+        self.is_send
+    }
+
     fn initialize(&mut self, world: &mut World) {
         let mut access = Access::new();
         F::Param::init_access(world, &mut access);
@@ -320,6 +408,7 @@ impl<Marker: 'static, F: SystemParamFunction<Marker>> System for FunctionSystem<
         if let Err(conflict) = access.validate() {
             panic!("{conflict}");
         }
+        self.is_send = !access.is_non_send();
 
         self.state = Some(FunctionSystemState {
             param_state: F::Param::init_state(world),
@@ -454,6 +543,7 @@ impl<Marker: 'static, F: SystemParamFunction<Marker>> IntoSystem<F::Out, Marker>
         FunctionSystem {
             f: self,
             state: None,
+            is_send: true,
             marker: Default::default(),
         }
     }
@@ -531,6 +621,92 @@ impl<'a, T: 'static> SystemParam for ResMut<'a, T> {
     }
 }
 
+/// A resource that isn't `Send`, inserted with [`World::insert_non_send_resource`]
+///
+/// Systems using it always run on the main thread
+pub struct NonSend<'a, T>(Ref<'a, T>);
+
+impl<'a, T: 'static> SystemParam for NonSend<'a, T> {
+    type State = ();
+
+    type Item<'w, 's> = NonSend<'w, T>;
+
+    fn init_state(world: &mut World) -> Self::State {
+        let _ = world;
+    }
+
+    fn init_access(_world: &mut World, access: &mut Access) {
+        access.add_non_send_read::<T>();
+    }
+
+    unsafe fn get_param<'w, 's>(
+        world: UnsafeWorldCell<'w>,
+        state: &'s mut Self::State,
+    ) -> Self::Item<'w, 's> {
+        let _ = state;
+        unsafe {
+            NonSend(
+                world
+                    .read_non_send_resource::<T>()
+                    .expect("Resource not found"),
+            )
+        }
+    }
+}
+
+impl<'a, T> Deref for NonSend<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Mutable access to a resource that isn't `Send`, see [`NonSend`]
+pub struct NonSendMut<'a, T>(RefMut<'a, T>);
+
+impl<'a, T: 'static> SystemParam for NonSendMut<'a, T> {
+    type State = ();
+
+    type Item<'w, 's> = NonSendMut<'w, T>;
+
+    fn init_state(world: &mut World) -> Self::State {
+        let _ = world;
+    }
+
+    fn init_access(_world: &mut World, access: &mut Access) {
+        access.add_non_send_write::<T>();
+    }
+
+    unsafe fn get_param<'w, 's>(
+        world: UnsafeWorldCell<'w>,
+        state: &'s mut Self::State,
+    ) -> Self::Item<'w, 's> {
+        let _ = state;
+        unsafe {
+            NonSendMut(
+                world
+                    .write_non_send_resource::<T>()
+                    .expect("Resource not found"),
+            )
+        }
+    }
+}
+
+impl<'a, T> Deref for NonSendMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'a, T> DerefMut for NonSendMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 pub struct Local<'s, T: Default + 'static>(pub(crate) &'s mut T);
 
 impl<'s, T: Default + 'static> Deref for Local<'s, T> {
@@ -573,6 +749,8 @@ impl<'a, T: Default + 'static> SystemParam for Local<'a, T> {
 
 #[cfg(test)]
 mod tests {
+    use std::{cell::Cell, rc::Rc};
+
     use crate::ecs::{component::Component, query::Query};
 
     use super::*;
@@ -604,6 +782,21 @@ mod tests {
         eprintln!("I should not run");
     }
 
+    /// Not `Send` because of the `Rc`
+    struct ScriptVm {
+        runs: Rc<Cell<u32>>,
+    }
+
+    fn run_script(vm: NonSend<ScriptVm>) {
+        vm.runs.set(vm.runs.get() + 1);
+    }
+
+    fn write_read_script(_vm: NonSend<ScriptVm>, _vm_mut: NonSendMut<ScriptVm>) {}
+
+    fn reload_script(mut vm: NonSendMut<ScriptVm>) {
+        vm.runs = Rc::new(Cell::new(100));
+    }
+
     #[test]
     #[should_panic(expected = "hello")]
     fn test_systems_work() {
@@ -670,4 +863,40 @@ mod tests {
 
         scheduler.run(world.as_unsafe_world_cell());
     }
+
+    #[test]
+    #[should_panic(expected = "uh oh, writing and reading non-send resource")]
+    fn test_access_validation_non_send_write_read() {
+        let mut world = World::new();
+        let mut scheduler = Scheduler::new();
+
+        world.insert_non_send_resource(ScriptVm {
+            runs: Rc::new(Cell::new(0)),
+        });
+
+        scheduler.add_system(write_read_script);
+
+        scheduler.initialize(&mut world);
+    }
+
+    #[test]
+    fn test_non_send_resources() {
+        let mut world = World::new();
+        let mut scheduler = Scheduler::new();
+
+        let runs = Rc::new(Cell::new(0));
+        world.insert_non_send_resource(ScriptVm { runs: runs.clone() });
+
+        scheduler.add_system(run_script);
+        scheduler.add_system(reload_script);
+        scheduler.add_system(run_script);
+        scheduler.initialize(&mut world);
+        assert!(scheduler.systems.iter().all(|system| !system.is_send()));
+
+        scheduler.run(world.as_unsafe_world_cell());
+        assert_eq!(runs.get(), 1);
+        let vm = world.remove_non_send_resource::<ScriptVm>().unwrap();
+        assert_eq!(vm.runs.get(), 101);
+        assert!(world.read_non_send_resource::<ScriptVm>().is_err());
+    }
 }
@@ -1,42 +1,100 @@
-use std::{alloc::Layout, mem::ManuallyDrop};
-
+use std::{
+    alloc::{self, Layout},
+    mem::ManuallyDrop,
+    ptr::NonNull,
+};
+
+/// A vector of values that all share the same [`Layout`], the type of the values is only known
+/// through their layout and drop function
 #[derive(Debug)]
 pub struct BlobVec {
     item_layout: Layout,
-    data: ManuallyDrop<Vec<u8>>,
-    drop_fn: fn(*mut ()),
+    drop: Option<unsafe fn(*mut u8)>,
+    data: NonNull<u8>,
+    len: usize,
+    capacity: usize,
 }
 
+// `BlobVec` owns its values like a `Vec` does
+unsafe impl Send for BlobVec {}
+unsafe impl Sync for BlobVec {}
+
 #[cfg(test)]
 impl PartialEq for BlobVec {
     fn eq(&self, other: &Self) -> bool {
-        self.item_layout == other.item_layout && self.data == other.data
+        self.item_layout == other.item_layout && self.bytes() == other.bytes()
     }
 }
 
+/// Drops the `T` behind the pointer, the type-erased drop function of `T` used by [`BlobVec`]
+/// and the type registry
+pub(crate) unsafe fn drop_ptr<T>(ptr: *mut u8) {
+    unsafe { ptr.cast::<T>().drop_in_place() }
+}
+
 impl BlobVec {
     pub fn new<T>() -> Self {
+        let drop = std::mem::needs_drop::<T>().then_some(drop_ptr::<T> as unsafe fn(*mut u8));
+
+        unsafe { Self::with_layout(Layout::new::<T>(), drop) }
+    }
+
+    /// Creates a vector for values with the layout, `drop` is called with a pointer to each value
+    /// that is dropped
+    ///
+    /// # Safety
+    /// `drop` MUST be safe to call with a pointer to any value pushed into the vector
+    pub unsafe fn with_layout(item_layout: Layout, drop: Option<unsafe fn(*mut u8)>) -> Self {
+        // Zero sized values never need an allocation
+        let capacity = if item_layout.size() == 0 {
+            usize::MAX
+        } else {
+            0
+        };
+
         Self {
-            item_layout: Layout::new::<T>(),
-            data: unsafe {
-                ManuallyDrop::new(std::mem::transmute::<std::vec::Vec<T>, std::vec::Vec<u8>>(
-                    Vec::<T>::new(),
-                ))
-            },
-            drop_fn: unsafe {
-                std::mem::transmute::<unsafe fn(*mut std::vec::Vec<T>), fn(*mut ())>(
-                    std::ptr::drop_in_place::<Vec<T>> as unsafe fn(*mut Vec<T>),
-                )
-            },
+            item_layout,
+            drop,
+            data: NonNull::new(std::ptr::without_provenance_mut(item_layout.align())).unwrap(),
+            len: 0,
+            capacity,
         }
     }
 
-    unsafe fn typed_ref<T>(&self) -> &Vec<T> {
-        unsafe { std::mem::transmute(&self.data) }
+    pub fn item_layout(&self) -> Layout {
+        self.item_layout
     }
 
-    unsafe fn typed_mut<T>(&mut self) -> &mut Vec<T> {
-        unsafe { std::mem::transmute(&mut self.data) }
+    /// The distance between two items, their size padded to keep every item aligned
+    fn stride(&self) -> usize {
+        self.item_layout.pad_to_align().size()
+    }
+
+    fn reserve_one(&mut self) {
+        if self.len < self.capacity {
+            return;
+        }
+
+        let new_capacity = self
+            .capacity
+            .checked_mul(2)
+            .expect("capacity overflow")
+            .max(4);
+        let new_layout = array_layout(self.item_layout.pad_to_align(), new_capacity);
+        let data = unsafe {
+            if self.capacity == 0 {
+                alloc::alloc(new_layout)
+            } else {
+                alloc::realloc(
+                    self.data.as_ptr(),
+                    array_layout(self.item_layout.pad_to_align(), self.capacity),
+                    new_layout.size(),
+                )
+            }
+        };
+
+        self.data = NonNull::new(data).unwrap_or_else(|| alloc::handle_alloc_error(new_layout));
+        self.capacity = new_capacity;
     }
 
     /// Pushes a new element of type `T` into the vector
@@ -49,7 +107,59 @@ impl BlobVec {
     pub unsafe fn push<T>(&mut self, item: T) {
         assert!(Layout::new::<T>() == self.item_layout);
 
-        unsafe { self.typed_mut().push(item) };
+        let mut item = ManuallyDrop::new(item);
+        unsafe { self.push_raw(NonNull::from(&mut *item).cast()) };
+    }
+
+    /// Moves the value behind the pointer to the end of the vector
+    ///
+    /// Only the `item_layout().size()` bytes of the value are read, it doesn't need the padding
+    /// that keeps the items of the vector aligned
+    ///
+    /// # Safety
+    /// `item` MUST point to a valid value of the vector item type, which must not be used or
+    /// dropped afterwards
+    pub unsafe fn push_raw(&mut self, item: NonNull<u8>) {
+        self.reserve_one();
+
+        unsafe {
+            let slot = self.data.add(self.len * self.stride());
+            std::ptr::copy_nonoverlapping(item.as_ptr(), slot.as_ptr(), self.item_layout.size());
+        }
+        self.len += 1;
+    }
+
+    /// Moves the value behind the pointer in place of the element at the given index, the old
+    /// element is dropped after the new one is written
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds
+    ///
+    /// # Safety
+    /// `item` MUST point to a valid value of the vector item type, which must not be used or
+    /// dropped afterwards
+    pub unsafe fn replace_raw(&mut self, index: usize, item: NonNull<u8>) {
+        let slot = self.get_ptr(index).expect("replace index should be < len");
+        let size = self.item_layout.size();
+
+        let Some(drop) = self.drop else {
+            unsafe { std::ptr::copy_nonoverlapping(item.as_ptr(), slot.as_ptr(), size) };
+            return;
+        };
+
+        // The old value is moved out before it's dropped, so the slot always holds a valid value
+        // even if the drop panics
+        let old = TempValue::new(self.item_layout);
+        unsafe {
+            std::ptr::copy_nonoverlapping(slot.as_ptr(), old.ptr.as_ptr(), size);
+            std::ptr::copy_nonoverlapping(item.as_ptr(), slot.as_ptr(), size);
+            drop(old.ptr.as_ptr());
+        }
+    }
+
+    /// Returns a pointer to the element at the given index
+    pub fn get_ptr(&self, index: usize) -> Option<NonNull<u8>> {
+        (index < self.len).then(|| unsafe { self.data.add(index * self.stride()) })
     }
 
     /// Returns a reference to the element at the given index
@@ -62,7 +172,8 @@ impl BlobVec {
     pub unsafe fn get<T>(&self, index: usize) -> Option<&T> {
         assert!(Layout::new::<T>() == self.item_layout);
 
-        unsafe { self.typed_ref().get(index) }
+        self.get_ptr(index)
+            .map(|ptr| unsafe { ptr.cast::<T>().as_ref() })
     }
 
     /// Returns a mutable reference to the element at the given index
@@ -75,87 +186,149 @@ impl BlobVec {
     pub unsafe fn get_mut<T>(&mut self, index: usize) -> Option<&mut T> {
         assert!(Layout::new::<T>() == self.item_layout);
 
-        unsafe { self.typed_mut().get_mut(index) }
+        self.get_ptr(index)
+            .map(|ptr| unsafe { ptr.cast::<T>().as_mut() })
     }
 
-    // TODO: handle 1 element
-    pub unsafe fn swap_remove(&mut self, index: usize) {
-        fn assert_failed(index: usize, len: usize) -> ! {
-            panic!("swap_remove index (is {index}) should be < len (is {len})");
-        }
+    /// Removes the element at the given index and drops it, the last element takes its place
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds
+    pub fn swap_remove_and_drop(&mut self, index: usize) {
+        let removed = self.swap_remove_and_forget(index);
 
-        let len = self.data.len();
-        if len == 1 {
-            unsafe { self.data.set_len(0) };
-            return;
+        if let Some(drop) = self.drop {
+            unsafe { drop(removed.as_ptr()) };
         }
+    }
 
-        let bytes_len = len * self.item_layout.size();
-        if index >= len {
-            assert_failed(index, len);
+    /// Removes the element at the given index without dropping it, the last element takes its
+    /// place
+    ///
+    /// Returns a pointer to the removed element, which can be moved elsewhere, e.g. into another
+    /// [`BlobVec`] with [`BlobVec::push_raw`]. The pointer is valid until the vector is changed,
+    /// the element is leaked if it isn't moved or dropped before that
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds
+    pub fn swap_remove_and_forget(&mut self, index: usize) -> NonNull<u8> {
+        let len = self.len;
+        assert!(
+            index < len,
+            "swap_remove index (is {index}) should be < len (is {len})"
+        );
+
+        let stride = self.stride();
+        let last = unsafe { self.data.add((len - 1) * stride) };
+        // The last element doesn't need to be moved
+        if index != len - 1 {
+            unsafe {
+                std::ptr::swap_nonoverlapping(
+                    self.data.add(index * stride).as_ptr(),
+                    last.as_ptr(),
+                    self.item_layout.size(),
+                )
+            };
         }
-        let base_ptr = self.data.as_mut_ptr();
-        unsafe {
-            std::ptr::swap_nonoverlapping(
-                base_ptr.add(bytes_len - self.item_layout.size()),
-                base_ptr.add(index * self.item_layout.size()),
-                self.item_layout.size(),
-            )
-        };
-        unsafe { self.data.set_len(len - 1) };
+        self.len -= 1;
+
+        last
     }
 
     pub fn len(&self) -> usize {
-        self.data.len()
+        self.len
     }
 
     pub fn is_empty(&self) -> bool {
-        self.data.len() == 0
+        self.len == 0
     }
 
     pub fn capacity(&self) -> usize {
-        self.data.capacity()
+        self.capacity
     }
 
     pub unsafe fn iter<T>(&self) -> std::slice::Iter<'_, T> {
         // TODO: Check if `T` has the same type id
         assert!(Layout::new::<T>() == self.item_layout);
 
-        let vec = unsafe {
-            std::mem::transmute::<&std::mem::ManuallyDrop<std::vec::Vec<u8>>, &std::vec::Vec<T>>(
-                &self.data,
-            )
-        };
-
-        vec.iter()
+        unsafe { std::slice::from_raw_parts(self.data.cast::<T>().as_ptr(), self.len) }.iter()
     }
 
     pub unsafe fn iter_mut<T>(&mut self) -> std::slice::IterMut<'_, T> {
         // TODO: Check if `T` has the same type id
         assert!(Layout::new::<T>() == self.item_layout);
 
-        let vec = unsafe {
-            std::mem::transmute::<
-                &mut std::mem::ManuallyDrop<std::vec::Vec<u8>>,
-                &mut std::vec::Vec<T>,
-            >(&mut self.data)
+        unsafe { std::slice::from_raw_parts_mut(self.data.cast::<T>().as_ptr(), self.len) }
+            .iter_mut()
+    }
+
+    #[cfg(test)]
+    fn bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.data.as_ptr(), self.len * self.stride()) }
+    }
+}
+
+/// A buffer for a single value that is deallocated, without dropping the value, when it goes out
+/// of scope
+struct TempValue {
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl TempValue {
+    fn new(layout: Layout) -> Self {
+        let ptr = if layout.size() == 0 {
+            NonNull::new(std::ptr::without_provenance_mut(layout.align())).unwrap()
+        } else {
+            let data = unsafe { alloc::alloc(layout) };
+            NonNull::new(data).unwrap_or_else(|| alloc::handle_alloc_error(layout))
         };
 
-        vec.iter_mut()
+        Self { ptr, layout }
+    }
+}
+
+impl Drop for TempValue {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            unsafe { alloc::dealloc(self.ptr.as_ptr(), self.layout) };
+        }
     }
 }
 
+/// The layout of `capacity` items, which is always a multiple of the item alignment
+fn array_layout(item_layout: Layout, capacity: usize) -> Layout {
+    let size = item_layout
+        .size()
+        .checked_mul(capacity)
+        .expect("capacity overflow");
+
+    Layout::from_size_align(size, item_layout.align()).expect("capacity overflow")
+}
+
 impl Drop for BlobVec {
     fn drop(&mut self) {
-        let v: &mut Vec<u8> = &mut self.data;
-        let v: *mut Vec<u8> = v as *mut _;
-        let v: *mut () = v as _;
-        (self.drop_fn)(v)
+        if let Some(drop) = self.drop {
+            for index in 0..self.len {
+                unsafe { drop(self.data.add(index * self.stride()).as_ptr()) };
+            }
+        }
+
+        if self.item_layout.size() != 0 && self.capacity != 0 {
+            unsafe {
+                alloc::dealloc(
+                    self.data.as_ptr(),
+                    array_layout(self.item_layout.pad_to_align(), self.capacity),
+                )
+            };
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{cell::Cell, rc::Rc};
+
     use super::*;
 
     #[derive(Debug, PartialEq, Eq)]
@@ -163,25 +336,35 @@ mod tests {
         something: u32,
     }
 
+    /// Counts how many times it was dropped
+    struct DropCounter {
+        value: u32,
+        drops: Rc<Cell<u32>>,
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
     #[test]
     fn test_blob_vec() {
         let mut vec = BlobVec::new::<SomeComponent>();
 
         unsafe { vec.push(SomeComponent { something: 10 }) };
 
-        eprintln!("{vec:?}");
-
         let component = unsafe { vec.get::<SomeComponent>(0) };
 
         assert_eq!(component, Some(&SomeComponent { something: 10 }));
 
         unsafe { vec.push(SomeComponent { something: 5 }) };
 
-        eprintln!("{vec:?}");
-
         let component = unsafe { vec.get::<SomeComponent>(1) };
 
         assert_eq!(component, Some(&SomeComponent { something: 5 }));
+        assert_eq!(vec.len(), 2);
+        assert_eq!(unsafe { vec.get::<SomeComponent>(2) }, None);
     }
 
     #[test]
@@ -192,7 +375,7 @@ mod tests {
         unsafe { vec.push(SomeComponent { something: 2 }) };
         unsafe { vec.push(SomeComponent { something: 3 }) };
 
-        unsafe { vec.swap_remove(0) };
+        vec.swap_remove_and_drop(0);
 
         let mut expected = BlobVec::new::<SomeComponent>();
         unsafe { expected.push(SomeComponent { something: 3 }) };
@@ -207,10 +390,205 @@ mod tests {
 
         unsafe { vec.push(SomeComponent { something: 1 }) };
 
-        unsafe { vec.swap_remove(0) };
+        vec.swap_remove_and_drop(0);
 
         let expected = BlobVec::new::<SomeComponent>();
 
         assert_eq!(vec, expected);
     }
+
+    #[test]
+    #[should_panic]
+    fn test_swap_remove_out_of_bounds() {
+        let mut vec = BlobVec::new::<SomeComponent>();
+        unsafe { vec.push(SomeComponent { something: 1 }) };
+
+        vec.swap_remove_and_drop(1);
+    }
+
+    #[test]
+    fn test_drop() {
+        let drops = Rc::new(Cell::new(0));
+        let mut vec = BlobVec::new::<DropCounter>();
+        for value in 0..5 {
+            unsafe {
+                vec.push(DropCounter {
+                    value,
+                    drops: drops.clone(),
+                })
+            };
+        }
+
+        vec.swap_remove_and_drop(1);
+        assert_eq!(drops.get(), 1);
+        assert_eq!(unsafe { vec.get::<DropCounter>(1) }.unwrap().value, 4);
+
+        let removed = vec.swap_remove_and_forget(0);
+        let removed = unsafe { removed.cast::<DropCounter>().read() };
+        assert_eq!(removed.value, 0);
+        assert_eq!(drops.get(), 1);
+        assert_eq!(unsafe { vec.get::<DropCounter>(0) }.unwrap().value, 3);
+
+        let mut other = BlobVec::new::<DropCounter>();
+        let moved = vec.swap_remove_and_forget(1);
+        unsafe { other.push_raw(moved) };
+        assert_eq!(unsafe { other.get::<DropCounter>(0) }.unwrap().value, 4);
+        assert_eq!(drops.get(), 1);
+
+        drop(removed);
+        drop(vec);
+        drop(other);
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let drops = Rc::new(Cell::new(0));
+
+        struct Marker;
+
+        let mut vec = BlobVec::new::<Marker>();
+        for _ in 0..3 {
+            unsafe { vec.push(Marker) };
+        }
+        assert_eq!(vec.len(), 3);
+        assert!(unsafe { vec.get::<Marker>(2) }.is_some());
+        vec.swap_remove_and_drop(0);
+        assert_eq!(unsafe { vec.iter::<Marker>() }.count(), 2);
+
+        let mut counters = BlobVec::new::<(Marker, DropCounter)>();
+        unsafe {
+            counters.push((
+                Marker,
+                DropCounter {
+                    value: 0,
+                    drops: drops.clone(),
+                },
+            ))
+        };
+        drop(counters);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn test_alignment_and_growth() {
+        #[repr(align(16))]
+        #[derive(Debug, PartialEq)]
+        struct Aligned(u8);
+
+        let mut vec = BlobVec::new::<Aligned>();
+        for value in 0..100 {
+            unsafe { vec.push(Aligned(value)) };
+        }
+
+        assert!(vec.capacity() >= 100);
+        for (index, value) in unsafe { vec.iter::<Aligned>() }.enumerate() {
+            assert_eq!(value, &Aligned(index as u8));
+            assert!((value as *const Aligned).is_aligned());
+        }
+    }
+
+    #[test]
+    fn test_with_layout() {
+        unsafe fn drop_string(ptr: *mut u8) {
+            unsafe { ptr.cast::<String>().drop_in_place() }
+        }
+
+        let mut vec = unsafe { BlobVec::with_layout(Layout::new::<String>(), Some(drop_string)) };
+        for value in ["a", "b"] {
+            let mut value = ManuallyDrop::new(value.to_string());
+            unsafe { vec.push_raw(NonNull::from(&mut *value).cast()) };
+        }
+
+        let mut replacement = ManuallyDrop::new("c".to_string());
+        unsafe { vec.replace_raw(0, NonNull::from(&mut *replacement).cast()) };
+
+        assert_eq!(unsafe { vec.get::<String>(0) }.unwrap(), "c");
+        assert_eq!(unsafe { vec.get::<String>(1) }.unwrap(), "b");
+    }
+
+    #[test]
+    fn test_replace() {
+        let drops = Rc::new(Cell::new(0));
+        let mut vec = BlobVec::new::<DropCounter>();
+        for value in 0..2 {
+            unsafe {
+                vec.push(DropCounter {
+                    value,
+                    drops: drops.clone(),
+                })
+            };
+        }
+
+        let item = ManuallyDrop::new(DropCounter {
+            value: 2,
+            drops: drops.clone(),
+        });
+        unsafe { vec.replace_raw(0, NonNull::from(&*item).cast()) };
+        assert_eq!(drops.get(), 1);
+        assert_eq!(unsafe { vec.get::<DropCounter>(0) }.unwrap().value, 2);
+        assert_eq!(unsafe { vec.get::<DropCounter>(1) }.unwrap().value, 1);
+
+        drop(vec);
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn test_replace_panicking_drop() {
+        struct PanicOnDrop {
+            panic: bool,
+            drops: Rc<Cell<u32>>,
+        }
+
+        impl Drop for PanicOnDrop {
+            fn drop(&mut self) {
+                self.drops.set(self.drops.get() + 1);
+                assert!(!self.panic, "drop panicked");
+            }
+        }
+
+        let drops = Rc::new(Cell::new(0));
+        let mut vec = BlobVec::new::<PanicOnDrop>();
+        unsafe {
+            vec.push(PanicOnDrop {
+                panic: true,
+                drops: drops.clone(),
+            })
+        };
+
+        let item = ManuallyDrop::new(PanicOnDrop {
+            panic: false,
+            drops: drops.clone(),
+        });
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+            vec.replace_raw(0, NonNull::from(&*item).cast())
+        }));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 1);
+        assert!(!unsafe { vec.get::<PanicOnDrop>(0) }.unwrap().panic);
+
+        drop(vec);
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn test_unpadded_layout() {
+        let layout = Layout::from_size_align(5, 4).unwrap();
+        let mut vec = unsafe { BlobVec::with_layout(layout, None) };
+
+        for value in 0..3u8 {
+            let mut bytes = [value; 5];
+            unsafe { vec.push_raw(NonNull::from(&mut bytes).cast()) };
+        }
+        let mut replacement = [9u8; 5];
+        unsafe { vec.replace_raw(1, NonNull::from(&mut replacement).cast()) };
+
+        assert_eq!(vec.item_layout(), layout);
+        for (index, expected) in [0, 9, 2].into_iter().enumerate() {
+            let ptr = vec.get_ptr(index).unwrap();
+            assert!(ptr.cast::<u32>().is_aligned());
+            let bytes = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), 5) };
+            assert_eq!(bytes, [expected; 5]);
+        }
+    }
 }
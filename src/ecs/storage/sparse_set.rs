@@ -25,6 +25,10 @@ impl<I: SparseIndex, V> SparseSet<I, V> {
         self.sparse.get(index).map(|si| &mut self.dense[*si])
     }
 
+    pub fn values_mut(&mut self) -> std::slice::IterMut<'_, V> {
+        self.dense.iter_mut()
+    }
+
     pub fn insert(&mut self, index: I, value: V) {
         if let Some(dense_index) = self.sparse.get(index.clone()) {
             // # Safety: if dense index exists, value always exists
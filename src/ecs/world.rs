@@ -1,20 +1,31 @@
 use std::{
     any::{Any, TypeId},
+    cell::{Ref, RefCell, RefMut},
     marker::PhantomData,
+    ptr::NonNull,
     sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+    thread::ThreadId,
 };
 
+use rustc_hash::FxHashMap;
+
 use crate::{
     anymap::AnyMap,
     ecs::scheduler::{Access, SystemParam},
 };
 
 use super::{
-    component::{Component, Components, ComponentsInfo, TupleAddComponent},
+    component::{
+        Component, ComponentDescriptor, ComponentHook, ComponentHooks, ComponentId, Components,
+        ComponentsInfo, TupleAddComponent,
+    },
     entity::Entity,
     events::{Event, EventRegistry, Events},
     generational_array::GenerationalIndexAllocator,
-    query::{ComponentAccessor, Query},
+    observer::Observers,
+    query::{ComponentAccessor, DynamicAccess, DynamicQuery, Query},
+    reflect::{Reflect, TypeRegistry},
+    removal::RemovedComponentEvents,
 };
 
 #[derive(Copy, Clone)]
@@ -48,12 +59,39 @@ impl<'w> UnsafeWorldCell<'w> {
         Ok(resource.write().unwrap())
     }
 
+    /// # Safety
+    /// The resource MUST NOT be removed from the world while the returned borrow is alive
+    pub unsafe fn read_non_send_resource<T: 'static>(self) -> Result<Ref<'w, T>, anyhow::Error> {
+        unsafe { (*self.world).read_non_send_resource() }
+    }
+
+    /// # Safety
+    /// The resource MUST NOT be removed from the world while the returned borrow is alive
+    pub unsafe fn write_non_send_resource<T: 'static>(
+        self,
+    ) -> Result<RefMut<'w, T>, anyhow::Error> {
+        unsafe { (*self.world).write_non_send_resource() }
+    }
+
+    /// Whether this is the thread the world was created on
+    pub fn is_main_thread(self) -> bool {
+        unsafe { (*self.world).main_thread == std::thread::current().id() }
+    }
+
     pub fn components_info(self) -> &'w ComponentsInfo {
         unsafe { &(*self.world).components_info }
     }
 
+    pub(crate) unsafe fn removed_components(self) -> &'w RemovedComponentEvents {
+        unsafe { &(*self.world).removed_components }
+    }
+
     pub unsafe fn get_component<T: 'static>(self, entity: Entity) -> Option<&'w T> {
         unsafe {
+            if !(*self.world).contains(entity) {
+                return None;
+            }
+
             let component_info = (*self.world)
                 .components_info
                 .get_by_type_id(TypeId::of::<T>())
@@ -68,6 +106,10 @@ impl<'w> UnsafeWorldCell<'w> {
 
     pub unsafe fn get_component_mut<T: 'static>(self, entity: Entity) -> Option<&'w mut T> {
         unsafe {
+            if !(*self.world).contains(entity) {
+                return None;
+            }
+
             let component_info = (*self.world)
                 .components_info
                 .get_by_type_id(TypeId::of::<T>())
@@ -102,12 +144,27 @@ pub struct World {
     pub(crate) components_info: ComponentsInfo,
     pub(crate) components: Components,
     resources: AnyMap,
+    /// Resources that aren't `Send`, only accessible from `main_thread`
+    non_send_resources: AnyMap,
+    main_thread: ThreadId,
+    resource_names: FxHashMap<TypeId, &'static str>,
+    non_send_resource_names: FxHashMap<TypeId, &'static str>,
+    type_registry: TypeRegistry,
+    pub(crate) observers: Observers,
+    removed_components: RemovedComponentEvents,
 }
 
 impl World {
     pub(crate) fn new() -> Self {
         Self {
             resources: AnyMap::new(),
+            non_send_resources: AnyMap::new(),
+            main_thread: std::thread::current().id(),
+            resource_names: FxHashMap::default(),
+            non_send_resource_names: FxHashMap::default(),
+            type_registry: TypeRegistry::new(),
+            observers: Observers::default(),
+            removed_components: RemovedComponentEvents::default(),
             components: Components::new(),
             components_info: ComponentsInfo::new(),
             entity_allocator: GenerationalIndexAllocator::new(),
@@ -117,9 +174,13 @@ impl World {
 
     pub fn insert_resource<T: 'static>(&mut self, resource: T) {
         self.resources.insert(RwLock::new(resource));
+        self.type_registry.register::<T>();
+        self.resource_names
+            .insert(TypeId::of::<T>(), std::any::type_name::<T>());
     }
 
     pub fn remove_resource<T: 'static>(&mut self) -> Option<T> {
+        self.resource_names.remove(&TypeId::of::<T>());
         self.resources
             .remove::<RwLock<T>>()
             .map(|r| r.into_inner().unwrap())
@@ -147,9 +208,111 @@ impl World {
         Ok(resource.write().unwrap())
     }
 
+    /// Inserts a resource that isn't `Send`, like a window or a scripting VM, which can only be
+    /// accessed from the thread that created the world
+    ///
+    /// Systems read it with [`NonSend`](super::scheduler::NonSend) and
+    /// [`NonSendMut`](super::scheduler::NonSendMut)
+    pub fn insert_non_send_resource<T: 'static>(&mut self, resource: T) {
+        self.assert_main_thread::<T>();
+
+        self.non_send_resources.insert(RefCell::new(resource));
+        self.non_send_resource_names
+            .insert(TypeId::of::<T>(), std::any::type_name::<T>());
+    }
+
+    pub fn remove_non_send_resource<T: 'static>(&mut self) -> Option<T> {
+        self.assert_main_thread::<T>();
+
+        self.non_send_resource_names.remove(&TypeId::of::<T>());
+        self.non_send_resources
+            .remove::<RefCell<T>>()
+            .map(RefCell::into_inner)
+    }
+
+    /// # Panics
+    /// Panics if called from another thread than the one that created the world
+    pub fn read_non_send_resource<T: 'static>(&self) -> Result<Ref<'_, T>, anyhow::Error> {
+        self.assert_main_thread::<T>();
+
+        let resource = self.non_send_resources.get::<RefCell<T>>().ok_or_else(|| {
+            anyhow::anyhow!("No such non-send resource {}", std::any::type_name::<T>())
+        })?;
+
+        Ok(resource.borrow())
+    }
+
+    /// # Panics
+    /// Panics if called from another thread than the one that created the world
+    pub fn write_non_send_resource<T: 'static>(&self) -> Result<RefMut<'_, T>, anyhow::Error> {
+        self.assert_main_thread::<T>();
+
+        let resource = self.non_send_resources.get::<RefCell<T>>().ok_or_else(|| {
+            anyhow::anyhow!("No such non-send resource {}", std::any::type_name::<T>())
+        })?;
+
+        Ok(resource.borrow_mut())
+    }
+
+    fn assert_main_thread<T>(&self) {
+        assert!(
+            self.main_thread == std::thread::current().id(),
+            "Non-send resource {} accessed off the main thread",
+            std::any::type_name::<T>()
+        );
+    }
+
+    /// The type names of all resources, in no particular order
+    pub fn resource_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.resource_names
+            .values()
+            .chain(self.non_send_resource_names.values())
+            .copied()
+    }
+
+    /// All entities that were added to the world
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
     pub fn register_component<T: Component>(&mut self) {
         let component_id = self.components_info.register_component::<T>();
         self.components.register_component::<T>(component_id);
+        self.type_registry.register::<T>();
+    }
+
+    /// Registers a component that is only known at runtime, its values are inserted with
+    /// [`World::insert_component_by_id_raw`] and read with [`World::get_component_by_id`] or
+    /// [`World::query_dynamic`]
+    pub fn register_dynamic_component(&mut self, descriptor: ComponentDescriptor) -> ComponentId {
+        let component_id = self.components_info.register_descriptor(&descriptor);
+        self.components
+            .register_descriptor(component_id, &descriptor);
+
+        component_id
+    }
+
+    /// Registers the reflection data of a component or resource type
+    pub fn register_type<T: Reflect>(&mut self) {
+        self.type_registry.register_reflect::<T>();
+    }
+
+    pub fn type_registry(&self) -> &TypeRegistry {
+        &self.type_registry
+    }
+
+    /// The hooks of a registered component, run when it's added to or removed from an entity
+    ///
+    /// # Panics
+    /// Panics if the component isn't registered
+    pub fn register_component_hooks<T: Component>(&mut self) -> &mut ComponentHooks {
+        let component_id = self
+            .components_info
+            .get::<T>()
+            .expect("component should be registered before its hooks")
+            .id();
+
+        self.components_info.hooks_mut(component_id)
     }
 
     pub fn add_entity<T: TupleAddComponent>(&mut self, components: T) -> Entity {
@@ -157,18 +320,138 @@ impl World {
         let entity = Entity::from(entity);
         self.entities.push(entity);
 
-        components.add_component(&self.components_info, &mut self.components, entity);
+        let mut inserted = Vec::new();
+        components.add_component(
+            &self.components_info,
+            &mut self.components,
+            entity,
+            &mut inserted,
+        );
+        self.run_insert_hooks(entity, &inserted);
 
         entity
     }
 
+    /// Whether the entity was added and not despawned yet
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entity_allocator.is_live(entity.into())
+    }
+
+    /// Removes the entity with all of its components, its children lose their
+    /// [`Parent`](super::hierarchy::Parent)
+    ///
+    /// Returns `false` if the entity was already despawned
+    pub fn despawn(&mut self, entity: Entity) -> bool {
+        if !self.contains(entity) {
+            return false;
+        }
+
+        self.detach(entity);
+
+        let removed: Vec<(ComponentId, Vec<ComponentHook>)> = self
+            .components_info
+            .iter()
+            .filter(|info| self.components.has_component(info.id(), entity))
+            .map(|info| (info.id(), info.hooks().get_on_remove().to_vec()))
+            .collect();
+        for on_remove in removed.iter().flat_map(|(_, on_remove)| on_remove) {
+            on_remove(self, entity);
+        }
+        // A hook may have despawned the entity already
+        if !self.contains(entity) {
+            return true;
+        }
+        for (component_id, _) in removed {
+            self.removed_components.send(component_id, entity);
+        }
+
+        self.observers.remove_entity(entity);
+        self.components.remove_entity(entity);
+        self.entities.retain(|e| *e != entity);
+        self.entity_allocator.deallocate(entity.into());
+
+        true
+    }
+
+    /// # Panics
+    /// Panics if the entity was despawned
     pub fn add_component<T: Component>(&mut self, entity: Entity, component: T) {
         let component_info = self
             .components_info
             .get_by_type_id(TypeId::of::<T>())
             .unwrap();
-        if let Some(component_sparse_set) = self.components.get_mut(component_info.id()) {
-            component_sparse_set.insert(entity, component);
+        self.insert_component_by_id(entity, component_info.id(), component);
+    }
+
+    /// Inserts a component whose type is only known to be `component_id`'s, running its hooks
+    pub(crate) fn insert_component_by_id<T: 'static>(
+        &mut self,
+        entity: Entity,
+        component_id: ComponentId,
+        component: T,
+    ) {
+        self.assert_contains(entity);
+
+        if let Some(component_sparse_set) = self.components.get_mut(component_id) {
+            let added = component_sparse_set.insert(entity, component);
+            self.run_insert_hooks(entity, &[(component_id, added)]);
+        }
+    }
+
+    /// Moves the value behind the pointer into the world as the component of the entity, used
+    /// for components registered with [`World::register_dynamic_component`]
+    ///
+    /// # Panics
+    /// Panics if the component isn't registered or the entity was despawned
+    ///
+    /// # Safety
+    /// `component` MUST point to a valid value of the component, which must not be used or
+    /// dropped afterwards
+    pub unsafe fn insert_component_by_id_raw(
+        &mut self,
+        entity: Entity,
+        component_id: ComponentId,
+        component: NonNull<u8>,
+    ) {
+        self.assert_contains(entity);
+
+        let component_sparse_set = self
+            .components
+            .get_mut(component_id)
+            .expect("component should be registered");
+        let added = unsafe { component_sparse_set.insert_raw(entity, component) };
+
+        self.run_insert_hooks(entity, &[(component_id, added)]);
+    }
+
+    /// Components are stored by entity index, which is reused after a despawn, so a despawned
+    /// entity would otherwise get the components of the entity that took its place
+    fn assert_contains(&self, entity: Entity) {
+        assert!(
+            self.contains(entity),
+            "{entity:?} was despawned, components can't be added to it"
+        );
+    }
+
+    /// Runs the `on_add` hooks of the components that were added, then the `on_insert` hooks of
+    /// all inserted components, including those that replaced a previous value
+    fn run_insert_hooks(&mut self, entity: Entity, inserted: &[(ComponentId, bool)]) {
+        let hooks: Vec<(ComponentHooks, bool)> = inserted
+            .iter()
+            .map(|&(component_id, added)| {
+                (self.components_info.hooks_mut(component_id).clone(), added)
+            })
+            .collect();
+
+        for (hooks, _) in hooks.iter().filter(|(_, added)| *added) {
+            for on_add in hooks.get_on_add() {
+                on_add(self, entity);
+            }
+        }
+        for (hooks, _) in &hooks {
+            for on_insert in hooks.get_on_insert() {
+                on_insert(self, entity);
+            }
         }
     }
 
@@ -177,9 +460,64 @@ impl World {
             .components_info
             .get_by_type_id(TypeId::of::<T>())
             .unwrap();
-        if let Some(component_sparse_set) = self.components.get_mut(component_info.id()) {
-            component_sparse_set.remove_entity(entity);
+        self.remove_component_by_id(entity, component_info.id());
+    }
+
+    pub fn remove_component_by_id(&mut self, entity: Entity, component_id: ComponentId) {
+        if !self.contains(entity) || !self.components.has_component(component_id, entity) {
+            return;
+        }
+
+        let on_remove = self
+            .components_info
+            .hooks_mut(component_id)
+            .get_on_remove()
+            .to_vec();
+        for on_remove in on_remove {
+            on_remove(self, entity);
+        }
+        // The hook may have removed the component or despawned the entity already
+        if self.contains(entity)
+            && let Some(component_sparse_set) = self.components.get_mut(component_id)
+            && component_sparse_set.remove_entity(entity)
+        {
+            self.removed_components.send(component_id, entity);
+        }
+    }
+
+    /// A pointer to the component of the entity, valid until the world is changed
+    pub fn get_component_by_id(
+        &self,
+        entity: Entity,
+        component_id: ComponentId,
+    ) -> Option<NonNull<u8>> {
+        if !self.contains(entity) {
+            return None;
         }
+
+        self.components.get(component_id)?.get_ptr(entity)
+    }
+
+    /// Returns `None` if the entity doesn't have the component or was despawned
+    pub fn get_component<T: Component>(&self, entity: Entity) -> Option<&T> {
+        if !self.contains(entity) {
+            return None;
+        }
+
+        let component_info = self.components_info.get::<T>()?;
+        self.components.get(component_info.id())?.get(entity)
+    }
+
+    /// Returns `None` if the entity doesn't have the component or was despawned
+    pub fn get_component_mut<T: Component>(&mut self, entity: Entity) -> Option<&mut T> {
+        if !self.contains(entity) {
+            return None;
+        }
+
+        let component_info = self.components_info.get::<T>()?;
+        self.components
+            .get_mut(component_info.id())?
+            .get_mut(entity)
     }
 
     pub fn iter_component<T: Component>(&self) -> std::slice::Iter<'_, T> {
@@ -193,6 +531,16 @@ impl World {
             .unwrap_or([].iter())
     }
 
+    /// Builds a query from component ids, for components that aren't known at compile time
+    ///
+    /// Fails if a component is written more than once or both read and written
+    pub fn query_dynamic(
+        &mut self,
+        accesses: &[DynamicAccess],
+    ) -> Result<DynamicQuery<'_>, anyhow::Error> {
+        DynamicQuery::new(self, accesses)
+    }
+
     pub fn query<T: ComponentAccessor + 'static>(&mut self) -> Query<'_, T> {
         let mut state = <Query<'_, T> as SystemParam>::init_state(self);
         let mut access = Access::new();
@@ -217,7 +565,10 @@ impl World {
         event_registry.register_event::<E>();
     }
 
+    /// Swaps the buffers of every event and of the removed components, see [`Events::update`]
     pub fn update_events(&mut self) {
+        self.removed_components.update();
+
         let this = std::ptr::from_mut(self);
         let registry = self.read_resource::<EventRegistry>().unwrap();
         unsafe {
@@ -264,6 +615,52 @@ mod tests {
         }
     }
 
+    struct Health(u32);
+
+    impl Component for Health {}
+
+    #[test]
+    fn test_despawned_entity_handle() {
+        let mut world = World::new();
+        world.register_component::<Health>();
+
+        let old = world.add_entity(Health(1));
+        world.despawn(old);
+        let new = world.add_entity(Health(2));
+        assert_eq!(new.index(), old.index());
+
+        assert!(world.get_component::<Health>(old).is_none());
+        assert!(world.get_component_mut::<Health>(old).is_none());
+        world.remove_component::<Health>(old);
+        assert_eq!(world.get_component::<Health>(new).unwrap().0, 2);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            world.add_component(old, Health(3))
+        }));
+        assert!(result.is_err());
+        assert_eq!(world.get_component::<Health>(new).unwrap().0, 2);
+    }
+
+    #[test]
+    fn test_resource_names() {
+        let mut world = World::new();
+
+        world.insert_resource(FPS(60));
+        world.insert_non_send_resource(FPS(30));
+        world.remove_non_send_resource::<FPS>();
+        assert_eq!(
+            world.resource_names().collect::<Vec<_>>(),
+            [std::any::type_name::<FPS>()]
+        );
+
+        world.insert_non_send_resource(FPS(30));
+        world.remove_resource::<FPS>();
+        assert_eq!(
+            world.resource_names().collect::<Vec<_>>(),
+            [std::any::type_name::<FPS>()]
+        );
+    }
+
     // #[test]
     // fn test_entity_with_components() {
 
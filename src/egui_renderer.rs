@@ -1,5 +1,5 @@
 use egui::epaint::Shadow;
-use egui::{Context, Visuals};
+use egui::{ClippedPrimitive, Context, FullOutput, RawInput, TexturesDelta, Visuals};
 use egui_wgpu::Renderer;
 use egui_wgpu::ScreenDescriptor;
 
@@ -9,22 +9,38 @@ use egui_winit::winit::event::WindowEvent;
 use egui_winit::winit::window::Window;
 use egui_winit::{EventResponse, State};
 
+/// The tessellated output of an egui pass, waiting to be drawn
+struct EguiFrame {
+    primitives: Vec<ClippedPrimitive>,
+    textures_delta: TexturesDelta,
+    pixels_per_point: f32,
+}
+
+impl Drop for EguiFrame {
+    fn drop(&mut self) {
+        // Undrawn texture changes don't matter once the renderer is gone, egui asserts they
+        // were applied otherwise
+        self.textures_delta.clear();
+    }
+}
+
 pub struct EguiRenderer {
     pub context: Context,
     state: State,
     renderer: Renderer,
+    frame: Option<EguiFrame>,
 }
 
 impl EguiRenderer {
     pub fn new(
+        context: Context,
         device: &Device,
         output_color_format: TextureFormat,
         output_depth_format: Option<TextureFormat>,
         msaa_samples: u32,
         window: &Window,
     ) -> EguiRenderer {
-        let egui_context = Context::default();
-        let id = egui_context.viewport_id();
+        let id = context.viewport_id();
 
         const BORDER_RADIUS: u8 = 2;
 
@@ -35,11 +51,10 @@ impl EguiRenderer {
             ..Default::default()
         };
 
-        egui_context.set_visuals(visuals);
+        context.set_visuals(visuals);
 
-        let egui_state = State::new(egui_context.clone(), id, &window, None, None, None);
+        let egui_state = State::new(context.clone(), id, &window, None, None, None);
 
-        // egui_state.set_pixels_per_point(window.scale_factor() as f32);
         let egui_renderer = Renderer::new(
             device,
             output_color_format,
@@ -52,9 +67,10 @@ impl EguiRenderer {
         );
 
         EguiRenderer {
-            context: egui_context,
+            context,
             state: egui_state,
             renderer: egui_renderer,
+            frame: None,
         }
     }
 
@@ -62,34 +78,67 @@ impl EguiRenderer {
         self.state.on_window_event(window, event)
     }
 
+    /// The input gathered since the previous pass
+    pub fn take_input(&mut self, window: &Window) -> RawInput {
+        self.state.take_egui_input(window)
+    }
+
+    /// Applies the platform output of a finished pass (e.g. the cursor icon) and tessellates its
+    /// shapes for the next [`EguiRenderer::draw`]
+    pub fn prepare(&mut self, window: &Window, full_output: FullOutput) {
+        self.state
+            .handle_platform_output(window, full_output.platform_output);
+
+        let primitives = self
+            .context
+            .tessellate(full_output.shapes, full_output.pixels_per_point);
+
+        // Texture changes of a pass that wasn't drawn still have to be applied
+        let mut textures_delta = self
+            .frame
+            .take()
+            .map(|mut frame| std::mem::take(&mut frame.textures_delta))
+            .unwrap_or_default();
+        textures_delta.append(full_output.textures_delta);
+
+        self.frame = Some(EguiFrame {
+            primitives,
+            textures_delta,
+            pixels_per_point: full_output.pixels_per_point,
+        });
+    }
+
+    /// Draws the prepared pass over the content of the view
     pub fn draw(
         &mut self,
         device: &Device,
         queue: &Queue,
         encoder: &mut CommandEncoder,
-        window: &Window,
         window_surface_view: &TextureView,
-        screen_descriptor: ScreenDescriptor,
-        mut run_ui: impl FnMut(&Context),
+        size_in_pixels: [u32; 2],
     ) {
-        // self.state.set_pixels_per_point(window.scale_factor() as f32);
-        let raw_input = self.state.take_egui_input(window);
-        let full_output = self.context.run_ui(raw_input, |context| {
-            run_ui(context);
-        });
+        let Some(frame) = self.frame.take() else {
+            return;
+        };
 
-        self.state
-            .handle_platform_output(window, full_output.platform_output);
+        let screen_descriptor = ScreenDescriptor {
+            size_in_pixels,
+            pixels_per_point: frame.pixels_per_point,
+        };
 
-        let tris = self
-            .context
-            .tessellate(full_output.shapes, full_output.pixels_per_point);
-        for (id, image_delta) in &full_output.textures_delta.set {
-            self.renderer
-                .update_texture(device, queue, *id, &image_delta[0]);
+        for (id, image_deltas) in &frame.textures_delta.set {
+            for image_delta in image_deltas {
+                self.renderer
+                    .update_texture(device, queue, *id, image_delta);
+            }
         }
-        self.renderer
-            .update_buffers(device, queue, encoder, &tris, &screen_descriptor);
+        self.renderer.update_buffers(
+            device,
+            queue,
+            encoder,
+            &frame.primitives,
+            &screen_descriptor,
+        );
         let mut rpass = encoder
             .begin_render_pass(&wgpu::RenderPassDescriptor {
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -108,9 +157,10 @@ impl EguiRenderer {
                 multiview_mask: None,
             })
             .forget_lifetime();
-        self.renderer.render(&mut rpass, &tris, &screen_descriptor);
+        self.renderer
+            .render(&mut rpass, &frame.primitives, &screen_descriptor);
         drop(rpass);
-        for x in &full_output.textures_delta.free {
+        for x in &frame.textures_delta.free {
             self.renderer.free_texture(x);
         }
     }
@@ -0,0 +1,456 @@
+use std::{collections::HashMap, hash::Hash, path::Path};
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use winit::{
+    event::MouseButton,
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+use crate::ecs::{
+    scheduler::{Access, Res, SystemParam},
+    world::{UnsafeWorldCell, World},
+};
+
+use super::Input;
+
+/// A physical button that can be bound to an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputButton {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl From<KeyCode> for InputButton {
+    fn from(value: KeyCode) -> Self {
+        InputButton::Key(value)
+    }
+}
+
+impl From<MouseButton> for InputButton {
+    fn from(value: MouseButton) -> Self {
+        InputButton::Mouse(value)
+    }
+}
+
+/// A modifier that has to be held for a [`Binding`] to trigger, either the left or right key counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Modifier {
+    Shift,
+    Control,
+    Alt,
+    Super,
+}
+
+impl Modifier {
+    fn keys(&self) -> &'static [KeyCode] {
+        match self {
+            Modifier::Shift => &[KeyCode::ShiftLeft, KeyCode::ShiftRight],
+            Modifier::Control => &[KeyCode::ControlLeft, KeyCode::ControlRight],
+            Modifier::Alt => &[KeyCode::AltLeft, KeyCode::AltRight],
+            Modifier::Super => &[KeyCode::SuperLeft, KeyCode::SuperRight, KeyCode::Meta],
+        }
+    }
+}
+
+/// A button, optionally combined with modifiers (e.g. `Ctrl + S`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Binding {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<Modifier>,
+    pub button: InputButton,
+}
+
+impl Binding {
+    pub fn new(button: impl Into<InputButton>) -> Self {
+        Self {
+            modifiers: Vec::new(),
+            button: button.into(),
+        }
+    }
+
+    pub fn with_modifier(mut self, modifier: Modifier) -> Self {
+        self.modifiers.push(modifier);
+        self
+    }
+
+    /// Returns if the button and all the modifiers of this binding are held
+    pub fn is_pressed(&self, input: &Input) -> bool {
+        self.modifiers
+            .iter()
+            .all(|modifier| modifier.keys().iter().any(|key| is_key_held(input, *key)))
+            && match self.button {
+                InputButton::Key(key) => is_key_held(input, key),
+                InputButton::Mouse(button) => input.is_mouse_pressed(button),
+            }
+    }
+}
+
+impl<T: Into<InputButton>> From<T> for Binding {
+    fn from(value: T) -> Self {
+        Binding::new(value)
+    }
+}
+
+fn is_key_held(input: &Input, key: KeyCode) -> bool {
+    let key = PhysicalKey::Code(key);
+    input.pressed_keys.contains(&key) || input.pressed_modifiers.contains(&key)
+}
+
+/// Bindings that produce an axis value from buttons
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AxisBinding {
+    /// Produces a value in `-1..=1`
+    OneD {
+        negative: Binding,
+        positive: Binding,
+    },
+    /// Produces a vector with each component in `-1..=1`, positive `y` is up
+    TwoD {
+        up: Binding,
+        down: Binding,
+        left: Binding,
+        right: Binding,
+    },
+}
+
+impl AxisBinding {
+    pub fn one_d(negative: impl Into<Binding>, positive: impl Into<Binding>) -> Self {
+        AxisBinding::OneD {
+            negative: negative.into(),
+            positive: positive.into(),
+        }
+    }
+
+    pub fn two_d(
+        up: impl Into<Binding>,
+        down: impl Into<Binding>,
+        left: impl Into<Binding>,
+        right: impl Into<Binding>,
+    ) -> Self {
+        AxisBinding::TwoD {
+            up: up.into(),
+            down: down.into(),
+            left: left.into(),
+            right: right.into(),
+        }
+    }
+
+    fn value(&self, input: &Input) -> Vec2 {
+        fn direction(input: &Input, negative: &Binding, positive: &Binding) -> f32 {
+            positive.is_pressed(input) as i32 as f32 - negative.is_pressed(input) as i32 as f32
+        }
+
+        match self {
+            AxisBinding::OneD { negative, positive } => {
+                Vec2::new(direction(input, negative, positive), 0.)
+            }
+            AxisBinding::TwoD {
+                up,
+                down,
+                left,
+                right,
+            } => Vec2::new(direction(input, left, right), direction(input, down, up)),
+        }
+    }
+}
+
+/// Maps user defined actions and axes to bindings
+///
+/// `A` and `X` are usually enums defined by the game, e.g. `Action::Fire` and `Axis::Move`.
+/// The map is a resource, so bindings can be changed at runtime through `ResMut<ActionMap<A, X>>`,
+/// and queried through the [`Actions`] system param
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "A: Serialize, X: Serialize",
+    deserialize = "A: DeserializeOwned + Eq + Hash, X: DeserializeOwned + Eq + Hash"
+))]
+pub struct ActionMap<A, X = ()> {
+    #[serde(default)]
+    actions: HashMap<A, Vec<Binding>>,
+    #[serde(default)]
+    axes: HashMap<X, Vec<AxisBinding>>,
+}
+
+impl<A: Eq + Hash, X: Eq + Hash> ActionMap<A, X> {
+    pub fn new() -> Self {
+        Self {
+            actions: HashMap::new(),
+            axes: HashMap::new(),
+        }
+    }
+
+    /// Adds a binding to the action, keeping the existing ones
+    pub fn bind(&mut self, action: A, binding: impl Into<Binding>) -> &mut Self {
+        self.actions.entry(action).or_default().push(binding.into());
+        self
+    }
+
+    /// Replaces all the bindings of the action
+    pub fn rebind(&mut self, action: A, bindings: Vec<Binding>) -> &mut Self {
+        self.actions.insert(action, bindings);
+        self
+    }
+
+    /// Removes all the bindings of the action
+    pub fn unbind(&mut self, action: &A) -> Option<Vec<Binding>> {
+        self.actions.remove(action)
+    }
+
+    pub fn bindings(&self, action: &A) -> &[Binding] {
+        self.actions
+            .get(action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Adds a binding to the axis, keeping the existing ones
+    pub fn bind_axis(&mut self, axis: X, binding: AxisBinding) -> &mut Self {
+        self.axes.entry(axis).or_default().push(binding);
+        self
+    }
+
+    /// Replaces all the bindings of the axis
+    pub fn rebind_axis(&mut self, axis: X, bindings: Vec<AxisBinding>) -> &mut Self {
+        self.axes.insert(axis, bindings);
+        self
+    }
+
+    /// Removes all the bindings of the axis
+    pub fn unbind_axis(&mut self, axis: &X) -> Option<Vec<AxisBinding>> {
+        self.axes.remove(axis)
+    }
+
+    pub fn axis_bindings(&self, axis: &X) -> &[AxisBinding] {
+        self.axes.get(axis).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns if any of the action bindings is held
+    pub fn pressed(&self, input: &Input, action: &A) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|binding| binding.is_pressed(input))
+    }
+
+    /// Returns the combined value of all the axis bindings, clamped to `-1..=1` on each component
+    pub fn axis_2d(&self, input: &Input, axis: &X) -> Vec2 {
+        self.axis_bindings(axis)
+            .iter()
+            .map(|binding| binding.value(input))
+            .sum::<Vec2>()
+            .clamp(Vec2::NEG_ONE, Vec2::ONE)
+    }
+}
+
+impl<A, X> ActionMap<A, X>
+where
+    A: Eq + Hash + Serialize + DeserializeOwned,
+    X: Eq + Hash + Serialize + DeserializeOwned,
+{
+    pub fn from_json(json: &str) -> Result<Self, anyhow::Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, anyhow::Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Loads bindings from a JSON config file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+
+        Self::from_json(&json)
+    }
+
+    /// Saves bindings to a JSON config file, which can be loaded back with [`ActionMap::load`]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+        std::fs::write(path, self.to_json()?)?;
+
+        Ok(())
+    }
+}
+
+impl<A: Eq + Hash, X: Eq + Hash> Default for ActionMap<A, X> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// System param to query the state of actions and axes defined in an [`ActionMap<A, X>`] resource
+pub struct Actions<'w, A: 'static, X: 'static = ()> {
+    map: Res<'w, ActionMap<A, X>>,
+    input: Res<'w, Input>,
+}
+
+impl<A: Eq + Hash, X: Eq + Hash> Actions<'_, A, X> {
+    /// Returns if the action is currently held
+    pub fn pressed(&self, action: A) -> bool {
+        self.map.pressed(&self.input, &action)
+    }
+
+    /// Returns the value of a 1D axis, for 2D axes this is the `x` component
+    pub fn axis(&self, axis: X) -> f32 {
+        self.map.axis_2d(&self.input, &axis).x
+    }
+
+    /// Returns the value of a 2D axis, for 1D axes the `y` component is always 0
+    pub fn axis_2d(&self, axis: X) -> Vec2 {
+        self.map.axis_2d(&self.input, &axis)
+    }
+}
+
+impl<A: 'static, X: 'static> SystemParam for Actions<'_, A, X> {
+    type State = <(Res<'static, ActionMap<A, X>>, Res<'static, Input>) as SystemParam>::State;
+
+    type Item<'world, 'state> = Actions<'world, A, X>;
+
+    fn init_state(world: &mut World) -> Self::State {
+        <(Res<'static, ActionMap<A, X>>, Res<'static, Input>) as SystemParam>::init_state(world)
+    }
+
+    fn init_access(world: &mut World, access: &mut Access) {
+        <(Res<'static, ActionMap<A, X>>, Res<'static, Input>) as SystemParam>::init_access(
+            world, access,
+        )
+    }
+
+    unsafe fn get_param<'w, 's>(
+        world: UnsafeWorldCell<'w>,
+        state: &'s mut Self::State,
+    ) -> Self::Item<'w, 's> {
+        let (map, input) = unsafe {
+            <(Res<'static, ActionMap<A, X>>, Res<'static, Input>) as SystemParam>::get_param(
+                world, state,
+            )
+        };
+
+        Actions { map, input }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    enum Action {
+        Fire,
+        Save,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    enum Axis {
+        Vertical,
+        Move,
+    }
+
+    fn press(input: &mut Input, key: KeyCode) {
+        input.pressed_keys.insert(PhysicalKey::Code(key));
+    }
+
+    #[test]
+    fn test_action_pressed() {
+        let mut map = ActionMap::<Action, Axis>::new();
+        map.bind(Action::Fire, KeyCode::Space)
+            .bind(Action::Fire, MouseButton::Left);
+
+        let mut input = Input::new();
+        assert!(!map.pressed(&input, &Action::Fire));
+
+        input.pressed_mouse_buttons.insert(MouseButton::Left);
+        assert!(map.pressed(&input, &Action::Fire));
+
+        input.pressed_mouse_buttons.clear();
+        press(&mut input, KeyCode::Space);
+        assert!(map.pressed(&input, &Action::Fire));
+
+        map.rebind(Action::Fire, vec![Binding::new(KeyCode::KeyF)]);
+        assert!(!map.pressed(&input, &Action::Fire));
+    }
+
+    #[test]
+    fn test_chord() {
+        let mut map = ActionMap::<Action, Axis>::new();
+        map.bind(
+            Action::Save,
+            Binding::new(KeyCode::KeyS).with_modifier(Modifier::Control),
+        );
+
+        let mut input = Input::new();
+        press(&mut input, KeyCode::KeyS);
+        assert!(!map.pressed(&input, &Action::Save));
+
+        input
+            .pressed_modifiers
+            .insert(PhysicalKey::Code(KeyCode::ControlRight));
+        assert!(map.pressed(&input, &Action::Save));
+    }
+
+    #[test]
+    fn test_axes() {
+        let mut map = ActionMap::<Action, Axis>::new();
+        map.bind_axis(
+            Axis::Vertical,
+            AxisBinding::one_d(KeyCode::KeyS, KeyCode::KeyW),
+        )
+        .bind_axis(
+            Axis::Vertical,
+            AxisBinding::one_d(KeyCode::ArrowDown, KeyCode::ArrowUp),
+        )
+        .bind_axis(
+            Axis::Move,
+            AxisBinding::two_d(KeyCode::KeyW, KeyCode::KeyS, KeyCode::KeyA, KeyCode::KeyD),
+        );
+
+        let mut input = Input::new();
+        press(&mut input, KeyCode::KeyW);
+        press(&mut input, KeyCode::ArrowUp);
+        press(&mut input, KeyCode::KeyA);
+
+        assert_eq!(map.axis_2d(&input, &Axis::Vertical), Vec2::new(1., 0.));
+        assert_eq!(map.axis_2d(&input, &Axis::Move), Vec2::new(-1., 1.));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut map = ActionMap::<Action, Axis>::new();
+        map.bind(Action::Fire, MouseButton::Left).bind(
+            Action::Save,
+            Binding::new(KeyCode::KeyS).with_modifier(Modifier::Control),
+        );
+        map.bind_axis(
+            Axis::Vertical,
+            AxisBinding::one_d(KeyCode::KeyS, KeyCode::KeyW),
+        );
+
+        let json = map.to_json().unwrap();
+        let loaded = ActionMap::<Action, Axis>::from_json(&json).unwrap();
+
+        assert_eq!(loaded.bindings(&Action::Fire), map.bindings(&Action::Fire));
+        assert_eq!(loaded.bindings(&Action::Save), map.bindings(&Action::Save));
+        assert_eq!(
+            loaded.axis_bindings(&Axis::Vertical),
+            map.axis_bindings(&Axis::Vertical)
+        );
+    }
+
+    #[test]
+    fn test_from_json() {
+        let map = ActionMap::<Action, Axis>::from_json(
+            r#"{
+                "actions": {
+                    "Fire": [{ "button": { "Key": "Space" } }],
+                    "Save": [{ "modifiers": ["Control"], "button": { "Key": "KeyS" } }]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(map.bindings(&Action::Fire), &[Binding::new(KeyCode::Space)]);
+        assert!(map.axis_bindings(&Axis::Move).is_empty());
+
+        assert!(ActionMap::<Action, Axis>::from_json(r#"{ "actions": { "Jump": [] } }"#).is_err());
+    }
+}
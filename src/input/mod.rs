@@ -0,0 +1,381 @@
+use std::collections::{HashMap, HashSet};
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use winit::{
+    event::MouseButton,
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+use crate::window::WindowRef;
+
+pub mod action;
+pub mod replay;
+pub mod text;
+pub mod touch;
+
+/// An input event consumed by the engine, decoupled from winit so it can be recorded and replayed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InputEvent {
+    Key {
+        key: PhysicalKey,
+        pressed: bool,
+    },
+    MouseButton {
+        button: MouseButton,
+        pressed: bool,
+    },
+    CursorMoved {
+        window: WindowRef,
+        x: f32,
+        y: f32,
+    },
+    CursorEntered {
+        window: WindowRef,
+    },
+    CursorLeft {
+        window: WindowRef,
+    },
+    MouseWheel {
+        delta: f32,
+    },
+    MouseMotion {
+        x: f32,
+        y: f32,
+    },
+    ScaleFactorChanged {
+        window: WindowRef,
+        scale_factor: f64,
+    },
+    Resized {
+        window: WindowRef,
+        width: f32,
+        height: f32,
+    },
+    Text {
+        text: String,
+    },
+    Ime(text::Ime),
+    Touch {
+        id: u64,
+        x: f32,
+        y: f32,
+        phase: touch::TouchPhase,
+    },
+    PinchGesture {
+        delta: f32,
+        phase: touch::TouchPhase,
+    },
+    PanGesture {
+        x: f32,
+        y: f32,
+        phase: touch::TouchPhase,
+    },
+    Moved {
+        window: WindowRef,
+        x: i32,
+        y: i32,
+    },
+    Focused {
+        window: WindowRef,
+        focused: bool,
+    },
+    CloseRequested {
+        window: WindowRef,
+    },
+}
+
+pub struct Input {
+    pub(crate) pressed_keys: HashSet<PhysicalKey>,
+    pub(crate) pressed_modifiers: HashSet<PhysicalKey>,
+    pub(crate) scroll_delta: f32,
+    pub(crate) pressed_mouse_buttons: HashSet<MouseButton>,
+    pub(crate) cursor_position: Option<Vec2>,
+    pub(crate) cursor_window: Option<WindowRef>,
+    pub(crate) mouse_delta: Vec2,
+    pub(crate) scale_factors: HashMap<WindowRef, f64>,
+}
+
+impl Input {
+    pub(crate) fn new() -> Self {
+        Self {
+            pressed_keys: HashSet::new(),
+            pressed_modifiers: HashSet::new(),
+            scroll_delta: 0.,
+            pressed_mouse_buttons: HashSet::new(),
+            cursor_position: None,
+            cursor_window: None,
+            mouse_delta: Vec2::ZERO,
+            scale_factors: HashMap::new(),
+        }
+    }
+
+    /// Updates the input state from an event, events that don't affect the input state are ignored
+    pub(crate) fn handle_event(&mut self, event: &InputEvent) {
+        match *event {
+            InputEvent::Key { key, pressed } => {
+                let keys = match key {
+                    PhysicalKey::Code(
+                        KeyCode::AltLeft
+                        | KeyCode::AltRight
+                        | KeyCode::ControlLeft
+                        | KeyCode::ControlRight
+                        | KeyCode::ShiftLeft
+                        | KeyCode::ShiftRight
+                        | KeyCode::Meta
+                        | KeyCode::SuperLeft
+                        | KeyCode::SuperRight
+                        | KeyCode::Hyper,
+                    ) => &mut self.pressed_modifiers,
+                    _ => &mut self.pressed_keys,
+                };
+
+                if pressed {
+                    keys.insert(key);
+                } else {
+                    keys.remove(&key);
+                }
+            }
+            InputEvent::MouseButton { button, pressed } => {
+                if pressed {
+                    self.pressed_mouse_buttons.insert(button);
+                } else {
+                    self.pressed_mouse_buttons.remove(&button);
+                }
+            }
+            InputEvent::CursorMoved { window, x, y } => {
+                self.cursor_position = Some(Vec2::new(x, y));
+                self.cursor_window = Some(window);
+            }
+            InputEvent::CursorEntered { window } => {
+                // The position is only known after the cursor moves in the window
+                if self.cursor_window != Some(window) {
+                    self.cursor_position = None;
+                }
+                self.cursor_window = Some(window);
+            }
+            InputEvent::CursorLeft { window } => {
+                if self.cursor_window == Some(window) {
+                    self.cursor_position = None;
+                    self.cursor_window = None;
+                }
+            }
+            InputEvent::MouseWheel { delta } => {
+                self.scroll_delta = delta;
+            }
+            InputEvent::MouseMotion { x, y } => {
+                self.mouse_delta += Vec2::new(x, y);
+            }
+            InputEvent::ScaleFactorChanged {
+                window,
+                scale_factor,
+            } => {
+                self.scale_factors.insert(window, scale_factor);
+            }
+            InputEvent::Focused { window: _, focused } => {
+                // release events are not delivered to unfocused windows, so keys would otherwise
+                // stay pressed
+                if !focused {
+                    self.pressed_keys.clear();
+                    self.pressed_modifiers.clear();
+                    self.pressed_mouse_buttons.clear();
+                }
+            }
+            InputEvent::Resized { .. }
+            | InputEvent::Moved { .. }
+            | InputEvent::CloseRequested { .. }
+            | InputEvent::Text { .. }
+            | InputEvent::Ime(_)
+            | InputEvent::Touch { .. }
+            | InputEvent::PinchGesture { .. }
+            | InputEvent::PanGesture { .. } => {}
+        }
+    }
+
+    /// Resets the per-frame state, called at the end of every frame
+    pub(crate) fn end_frame(&mut self) {
+        self.scroll_delta = 0.;
+        self.mouse_delta = Vec2::ZERO;
+    }
+
+    /// Returns if the provided key is currently pressed/held in this frame
+    pub fn is_pressed(&self, key: KeyCode) -> bool {
+        self.pressed_keys.contains(&PhysicalKey::Code(key))
+    }
+
+    /// scroll delta from this frame
+    pub fn scroll_delta(&self) -> f32 {
+        self.scroll_delta
+    }
+
+    /// Returns if the provided mouse button is currently pressed/held in this frame
+    pub fn is_mouse_pressed(&self, button: MouseButton) -> bool {
+        self.pressed_mouse_buttons.contains(&button)
+    }
+
+    /// Cursor position in physical pixels, relative to the top-left corner of the window returned
+    /// by [`Input::cursor_window`]
+    ///
+    /// Returns `None` if the cursor is outside all windows
+    pub fn cursor_position(&self) -> Option<Vec2> {
+        self.cursor_position
+    }
+
+    /// Cursor position in logical pixels (physical position divided by the scale factor of the
+    /// window returned by [`Input::cursor_window`])
+    ///
+    /// Returns `None` if the cursor is outside all windows
+    pub fn cursor_logical_position(&self) -> Option<Vec2> {
+        let scale_factor = self
+            .cursor_window
+            .and_then(|window| self.scale_factors.get(&window))
+            .copied()
+            .unwrap_or(1.);
+
+        self.cursor_position
+            .map(|position| position / scale_factor as f32)
+    }
+
+    /// Returns if the cursor is currently inside a window
+    pub fn is_cursor_in_window(&self) -> bool {
+        self.cursor_window.is_some()
+    }
+
+    /// The window the cursor is currently in
+    pub fn cursor_window(&self) -> Option<WindowRef> {
+        self.cursor_window
+    }
+
+    /// Raw mouse motion accumulated during this frame
+    ///
+    /// Unlike the cursor position this is not affected by the cursor being clamped to the window
+    /// edges, which makes it suitable for things like camera controls
+    pub fn mouse_delta(&self) -> Vec2 {
+        self.mouse_delta
+    }
+}
+
+pub mod keyboard {
+    pub use winit::keyboard::KeyCode;
+}
+
+pub mod mouse {
+    pub use winit::event::MouseButton;
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::{
+        dpi::PhysicalPosition,
+        event::{DeviceId, WindowEvent},
+    };
+
+    use crate::State;
+
+    use super::*;
+
+    #[test]
+    fn test_cursor_enter_and_leave() {
+        let mut state = State::new();
+        state.initialize();
+        let other = WindowRef::Entity(state.world.add_entity(()));
+        let input = |state: &State| {
+            let input = state.world.read_resource::<Input>().unwrap();
+            (input.cursor_window(), input.cursor_position())
+        };
+
+        state.handle_window_input(
+            WindowRef::Primary,
+            WindowEvent::CursorEntered {
+                device_id: DeviceId::dummy(),
+            },
+        );
+        assert_eq!(input(&state), (Some(WindowRef::Primary), None));
+        assert!(
+            state
+                .world
+                .read_resource::<Input>()
+                .unwrap()
+                .is_cursor_in_window()
+        );
+
+        state.handle_window_input(
+            WindowRef::Primary,
+            WindowEvent::CursorMoved {
+                device_id: DeviceId::dummy(),
+                position: PhysicalPosition::new(10., 20.),
+            },
+        );
+        assert_eq!(
+            input(&state),
+            (Some(WindowRef::Primary), Some(Vec2::new(10., 20.)))
+        );
+
+        // Entering another window before leaving the first one, which winit doesn't order
+        state.handle_window_input(
+            other,
+            WindowEvent::CursorEntered {
+                device_id: DeviceId::dummy(),
+            },
+        );
+        state.handle_window_input(
+            WindowRef::Primary,
+            WindowEvent::CursorLeft {
+                device_id: DeviceId::dummy(),
+            },
+        );
+        assert_eq!(input(&state), (Some(other), None));
+
+        state.handle_window_input(
+            other,
+            WindowEvent::CursorLeft {
+                device_id: DeviceId::dummy(),
+            },
+        );
+        assert_eq!(input(&state), (None, None));
+        assert!(
+            !state
+                .world
+                .read_resource::<Input>()
+                .unwrap()
+                .is_cursor_in_window()
+        );
+    }
+
+    #[test]
+    fn test_cursor_logical_position_per_window() {
+        let mut state = State::new();
+        state.initialize();
+        let other = WindowRef::Entity(state.world.add_entity(()));
+        state.handle_input_event(InputEvent::ScaleFactorChanged {
+            window: WindowRef::Primary,
+            scale_factor: 1.,
+        });
+        state.handle_input_event(InputEvent::ScaleFactorChanged {
+            window: other,
+            scale_factor: 2.,
+        });
+
+        state.handle_window_input(
+            other,
+            WindowEvent::CursorMoved {
+                device_id: DeviceId::dummy(),
+                position: PhysicalPosition::new(10., 20.),
+            },
+        );
+        let logical_position = |state: &State| {
+            let input = state.world.read_resource::<Input>().unwrap();
+            input.cursor_logical_position()
+        };
+        assert_eq!(logical_position(&state), Some(Vec2::new(5., 10.)));
+
+        state.handle_window_input(
+            WindowRef::Primary,
+            WindowEvent::CursorMoved {
+                device_id: DeviceId::dummy(),
+                position: PhysicalPosition::new(10., 20.),
+            },
+        );
+        assert_eq!(logical_position(&state), Some(Vec2::new(10., 20.)));
+    }
+}
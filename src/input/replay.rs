@@ -0,0 +1,213 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::InputEvent;
+
+/// Input events consumed during a single frame
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub frame: u64,
+    /// The [`Time::delta_time`](crate::time::Time::delta_time) of the frame
+    pub delta: f32,
+    pub events: Vec<InputEvent>,
+}
+
+/// Every input event consumed by the app, grouped by frame
+///
+/// Recordings are stored as CBOR and can be replayed headlessly with [`App::replay`](crate::App::replay)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    frames: Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    pub fn new() -> Self {
+        Self { frames: Vec::new() }
+    }
+
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+
+    pub fn push_frame(&mut self, frame: RecordedFrame) {
+        self.frames.push(frame);
+    }
+
+    pub fn from_reader(reader: impl Read) -> Result<Self, anyhow::Error> {
+        Ok(ciborium::from_reader(reader)?)
+    }
+
+    pub fn to_writer(&self, writer: impl Write) -> Result<(), anyhow::Error> {
+        Ok(ciborium::into_writer(self, writer)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open {}: {e}", path.display()))?;
+
+        Self::from_reader(BufReader::new(file))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .map_err(|e| anyhow::anyhow!("Failed to create {}: {e}", path.display()))?;
+
+        self.to_writer(BufWriter::new(file))
+    }
+}
+
+/// Collects input events as they are consumed and saves them when the app exits
+pub(crate) struct InputRecorder {
+    recording: InputRecording,
+    pending: Vec<InputEvent>,
+    path: PathBuf,
+}
+
+impl InputRecorder {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            recording: InputRecording::new(),
+            pending: Vec::new(),
+            path,
+        }
+    }
+
+    pub(crate) fn record(&mut self, event: InputEvent) {
+        self.pending.push(event);
+    }
+
+    /// Attaches every event recorded since the previous frame to this frame
+    pub(crate) fn start_frame(&mut self, frame: u64, delta: f32) {
+        self.recording.push_frame(RecordedFrame {
+            frame,
+            delta,
+            events: std::mem::take(&mut self.pending),
+        });
+    }
+
+    pub(crate) fn save(&self) -> Result<(), anyhow::Error> {
+        self.recording.save(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::keyboard::{KeyCode, PhysicalKey};
+
+    use crate::{
+        App,
+        ecs::{Component, query::Query, scheduler::Res},
+        input::Input,
+        time::Time,
+        window::WindowRef,
+    };
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(f32);
+
+    impl Component for Position {}
+
+    fn move_right(positions: Query<&mut Position>, input: Res<Input>, time: Res<Time>) {
+        for (_e, position) in positions.iter() {
+            if input.is_pressed(KeyCode::KeyD) {
+                position.0 += 10. * time.delta_time();
+            }
+        }
+    }
+
+    fn key(key: KeyCode, pressed: bool) -> InputEvent {
+        InputEvent::Key {
+            key: PhysicalKey::Code(key),
+            pressed,
+        }
+    }
+
+    fn recording() -> InputRecording {
+        let mut recording = InputRecording::new();
+        recording.push_frame(RecordedFrame {
+            frame: 1,
+            delta: 0.5,
+            events: vec![key(KeyCode::KeyD, true)],
+        });
+        recording.push_frame(RecordedFrame {
+            frame: 2,
+            delta: 0.25,
+            events: vec![],
+        });
+        recording.push_frame(RecordedFrame {
+            frame: 3,
+            delta: 1.,
+            events: vec![key(KeyCode::KeyD, false)],
+        });
+
+        recording
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut app = App::new()
+            .register_component::<Position>()
+            .add_system(move_right);
+        let entity = app.add_entity(Position(0.));
+
+        app.replay(&recording());
+
+        let world = app.world_mut();
+        let position = world.query::<&Position>().iter().next().unwrap();
+        assert_eq!(position, (entity, &Position(7.5)));
+        assert_eq!(world.read_resource::<Time>().unwrap().frame_count(), 3);
+    }
+
+    #[test]
+    fn test_replay_records_identical_input() {
+        let recording = recording();
+
+        let mut app = App::new().record_input("unused");
+        app.replay(&recording);
+
+        assert_eq!(app.state.recorder.unwrap().recording, recording);
+    }
+
+    #[test]
+    fn test_recording_round_trip() {
+        let mut recording = InputRecording::new();
+        recording.push_frame(RecordedFrame {
+            frame: 1,
+            delta: 0.016,
+            events: vec![
+                InputEvent::Resized {
+                    window: WindowRef::Primary,
+                    width: 800.,
+                    height: 600.,
+                },
+                InputEvent::Key {
+                    key: PhysicalKey::Code(KeyCode::KeyW),
+                    pressed: true,
+                },
+            ],
+        });
+        recording.push_frame(RecordedFrame {
+            frame: 2,
+            delta: 0.017,
+            events: vec![InputEvent::CursorMoved {
+                window: WindowRef::Primary,
+                x: 10.,
+                y: 20.,
+            }],
+        });
+
+        let mut bytes = Vec::new();
+        recording.to_writer(&mut bytes).unwrap();
+
+        assert_eq!(InputRecording::from_reader(&bytes[..]).unwrap(), recording);
+    }
+}
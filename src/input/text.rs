@@ -0,0 +1,132 @@
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::events::Event;
+
+/// Text produced by a key press, taking the keyboard layout and modifiers into account
+///
+/// This can contain control characters (e.g. `'\u{8}'` for backspace), text entry fields that want
+/// to handle those should check [`char::is_control`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextInput {
+    pub text: String,
+}
+
+impl Event for TextInput {}
+
+/// Input method editor events, only sent while IME is allowed through [`ImeSettings`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Ime {
+    /// The IME was enabled, preedit and commit events may follow
+    Enabled,
+    /// The text being composed, with the cursor range as byte offsets into the text
+    ///
+    /// An empty text means the preedit was cleared
+    Preedit {
+        text: String,
+        cursor: Option<(usize, usize)>,
+    },
+    /// Text that should be inserted into the text entry field
+    Commit(String),
+    /// The IME was disabled, any preedit text should be discarded
+    Disabled,
+}
+
+impl Event for Ime {}
+
+impl From<winit::event::Ime> for Ime {
+    fn from(value: winit::event::Ime) -> Self {
+        match value {
+            winit::event::Ime::Enabled => Ime::Enabled,
+            winit::event::Ime::Preedit(text, cursor) => Ime::Preedit { text, cursor },
+            winit::event::Ime::Commit(text) => Ime::Commit(text),
+            winit::event::Ime::Disabled => Ime::Disabled,
+        }
+    }
+}
+
+/// Controls the input method editor of the window, changes are applied at the end of the frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImeSettings {
+    /// Whether the IME is allowed, it should only be allowed while a text field is focused
+    pub enabled: bool,
+    /// Top-left corner of the area being edited in physical pixels, the IME candidate window is
+    /// placed around it
+    pub cursor_position: Vec2,
+    /// Size of the area being edited in physical pixels
+    pub cursor_size: Vec2,
+}
+
+impl ImeSettings {
+    pub(crate) fn new() -> Self {
+        Self {
+            enabled: false,
+            cursor_position: Vec2::ZERO,
+            cursor_size: Vec2::ZERO,
+        }
+    }
+
+    pub(crate) fn apply(&self, window: &winit::window::Window) {
+        window.set_ime_allowed(self.enabled);
+        window.set_ime_cursor_area(
+            winit::dpi::PhysicalPosition::new(self.cursor_position.x, self.cursor_position.y),
+            winit::dpi::PhysicalSize::new(self.cursor_size.x, self.cursor_size.y),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        State,
+        ecs::{events::EventReader, scheduler::ResMut},
+        input::InputEvent,
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Received {
+        text: String,
+        ime: Vec<Ime>,
+    }
+
+    fn read_text(
+        mut text_input: EventReader<TextInput>,
+        mut ime: EventReader<Ime>,
+        mut received: ResMut<Received>,
+    ) {
+        received
+            .text
+            .extend(text_input.read().map(|t| t.text.as_str()));
+        received.ime.extend(ime.read().cloned());
+    }
+
+    #[test]
+    fn test_text_and_ime_events() {
+        let mut state = State::new();
+
+        state.handle_input_event(InputEvent::Text {
+            text: "h".to_string(),
+        });
+        state.handle_input_event(InputEvent::Ime(Ime::Enabled));
+        state.handle_input_event(InputEvent::Text {
+            text: "i".to_string(),
+        });
+        state.handle_input_event(InputEvent::Ime(Ime::Commit("مرحبا".to_string())));
+
+        state.world.insert_resource(Received::default());
+        state.scheduler.add_system(read_text);
+
+        state.initialize();
+
+        state.run_frame();
+
+        let received = state.world.read_resource::<Received>().unwrap();
+        assert_eq!(received.text, "hi");
+        assert_eq!(
+            received.ime,
+            [Ime::Enabled, Ime::Commit("مرحبا".to_string())]
+        );
+    }
+}
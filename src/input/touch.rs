@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use glam::Vec2;
+pub use winit::event::TouchPhase;
+
+use crate::ecs::events::Event;
+
+use super::InputEvent;
+
+/// A finger touching the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Touch {
+    id: u64,
+    start_position: Vec2,
+    previous_position: Vec2,
+    position: Vec2,
+    phase: TouchPhase,
+}
+
+impl Touch {
+    /// Identifies the finger for as long as it touches the screen, ids may be reused afterwards
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Position in physical pixels where the touch started
+    pub fn start_position(&self) -> Vec2 {
+        self.start_position
+    }
+
+    /// Position in physical pixels at the end of the previous frame
+    pub fn previous_position(&self) -> Vec2 {
+        self.previous_position
+    }
+
+    /// Current position in physical pixels
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    /// How much the touch moved during this frame
+    pub fn delta(&self) -> Vec2 {
+        self.position - self.previous_position
+    }
+
+    /// How much the touch moved since it started
+    pub fn distance(&self) -> Vec2 {
+        self.position - self.start_position
+    }
+
+    pub fn phase(&self) -> TouchPhase {
+        self.phase
+    }
+}
+
+/// Keeps track of the touches on the screen
+pub struct Touches {
+    active: HashMap<u64, Touch>,
+    just_started: HashMap<u64, Touch>,
+    just_ended: HashMap<u64, Touch>,
+    just_cancelled: HashMap<u64, Touch>,
+}
+
+impl Touches {
+    pub(crate) fn new() -> Self {
+        Self {
+            active: HashMap::new(),
+            just_started: HashMap::new(),
+            just_ended: HashMap::new(),
+            just_cancelled: HashMap::new(),
+        }
+    }
+
+    /// Returns an active touch by id
+    pub fn get(&self, id: u64) -> Option<&Touch> {
+        self.active.get(&id)
+    }
+
+    /// Iterates over the touches currently on the screen
+    pub fn iter(&self) -> impl Iterator<Item = &Touch> {
+        self.active.values()
+    }
+
+    /// Returns if the touch started in this frame
+    pub fn just_started(&self, id: u64) -> bool {
+        self.just_started.contains_key(&id)
+    }
+
+    pub fn iter_just_started(&self) -> impl Iterator<Item = &Touch> {
+        self.just_started.values()
+    }
+
+    /// Returns if the finger was lifted in this frame
+    pub fn just_ended(&self, id: u64) -> bool {
+        self.just_ended.contains_key(&id)
+    }
+
+    pub fn iter_just_ended(&self) -> impl Iterator<Item = &Touch> {
+        self.just_ended.values()
+    }
+
+    /// Returns if the system cancelled the touch in this frame (e.g. the window lost focus)
+    pub fn just_cancelled(&self, id: u64) -> bool {
+        self.just_cancelled.contains_key(&id)
+    }
+
+    pub fn iter_just_cancelled(&self) -> impl Iterator<Item = &Touch> {
+        self.just_cancelled.values()
+    }
+
+    pub(crate) fn handle_touch(&mut self, id: u64, position: Vec2, phase: TouchPhase) {
+        match phase {
+            TouchPhase::Started => {
+                let touch = Touch {
+                    id,
+                    start_position: position,
+                    previous_position: position,
+                    position,
+                    phase,
+                };
+                self.active.insert(id, touch);
+                self.just_started.insert(id, touch);
+            }
+            TouchPhase::Moved => {
+                if let Some(touch) = self.active.get_mut(&id) {
+                    touch.position = position;
+                    touch.phase = phase;
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                let Some(mut touch) = self.active.remove(&id) else {
+                    return;
+                };
+                touch.position = position;
+                touch.phase = phase;
+
+                if phase == TouchPhase::Ended {
+                    self.just_ended.insert(id, touch);
+                } else {
+                    self.just_cancelled.insert(id, touch);
+                }
+            }
+        }
+    }
+
+    /// Resets the per-frame state, called at the end of every frame
+    pub(crate) fn end_frame(&mut self) {
+        self.just_started.clear();
+        self.just_ended.clear();
+        self.just_cancelled.clear();
+
+        for touch in self.active.values_mut() {
+            touch.previous_position = touch.position;
+        }
+    }
+}
+
+/// Two finger pinch, positive deltas mean zooming in
+///
+/// Only sent on macOS and iOS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PinchGesture {
+    pub delta: f32,
+    pub phase: TouchPhase,
+}
+
+impl Event for PinchGesture {}
+
+/// Multi finger pan, the delta is in physical pixels since the last pan event
+///
+/// Only sent on iOS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PanGesture {
+    pub delta: Vec2,
+    pub phase: TouchPhase,
+}
+
+impl Event for PanGesture {}
+
+impl From<winit::event::Touch> for InputEvent {
+    fn from(value: winit::event::Touch) -> Self {
+        InputEvent::Touch {
+            id: value.id,
+            x: value.location.x as f32,
+            y: value.location.y as f32,
+            phase: value.phase,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::{
+        dpi::PhysicalPosition,
+        event::{DeviceId, WindowEvent},
+    };
+
+    use crate::{
+        State,
+        ecs::{events::EventReader, scheduler::ResMut},
+        window::WindowRef,
+    };
+
+    use super::*;
+
+    fn touch(id: u64, x: f64, y: f64, phase: TouchPhase) -> WindowEvent {
+        WindowEvent::Touch(winit::event::Touch {
+            device_id: DeviceId::dummy(),
+            phase,
+            location: PhysicalPosition::new(x, y),
+            force: None,
+            id,
+        })
+    }
+
+    #[test]
+    fn test_touches() {
+        let mut state = State::new();
+        state.initialize();
+
+        state.handle_window_input(WindowRef::Primary, touch(0, 10., 10., TouchPhase::Started));
+        state.handle_window_input(WindowRef::Primary, touch(1, 50., 50., TouchPhase::Started));
+        state.handle_window_input(WindowRef::Primary, touch(0, 20., 15., TouchPhase::Moved));
+
+        {
+            let touches = state.world.read_resource::<Touches>().unwrap();
+            assert!(touches.just_started(0));
+            assert!(touches.just_started(1));
+            assert_eq!(touches.iter().count(), 2);
+
+            let touch = touches.get(0).unwrap();
+            assert_eq!(touch.start_position(), Vec2::new(10., 10.));
+            assert_eq!(touch.position(), Vec2::new(20., 15.));
+        }
+
+        state.run_frame();
+
+        state.handle_window_input(WindowRef::Primary, touch(0, 30., 15., TouchPhase::Moved));
+        state.handle_window_input(WindowRef::Primary, touch(1, 50., 50., TouchPhase::Ended));
+
+        let touches = state.world.read_resource::<Touches>().unwrap();
+        assert!(!touches.just_started(0));
+        assert!(touches.just_ended(1));
+        assert!(touches.get(1).is_none());
+        assert_eq!(touches.get(0).unwrap().delta(), Vec2::new(10., 0.));
+        assert_eq!(touches.get(0).unwrap().distance(), Vec2::new(20., 5.));
+    }
+
+    #[derive(Default)]
+    struct Received {
+        pinch: Vec<PinchGesture>,
+        pan: Vec<PanGesture>,
+    }
+
+    fn read_gestures(
+        mut pinch: EventReader<PinchGesture>,
+        mut pan: EventReader<PanGesture>,
+        mut received: ResMut<Received>,
+    ) {
+        received.pinch.extend(pinch.read().copied());
+        received.pan.extend(pan.read().copied());
+    }
+
+    #[test]
+    fn test_gestures() {
+        let mut state = State::new();
+
+        state.handle_window_input(
+            WindowRef::Primary,
+            WindowEvent::PinchGesture {
+                device_id: DeviceId::dummy(),
+                delta: 0.5,
+                phase: TouchPhase::Moved,
+            },
+        );
+        state.handle_window_input(
+            WindowRef::Primary,
+            WindowEvent::PanGesture {
+                device_id: DeviceId::dummy(),
+                delta: PhysicalPosition::new(3., -4.),
+                phase: TouchPhase::Started,
+            },
+        );
+
+        state.world.insert_resource(Received::default());
+        state.scheduler.add_system(read_gestures);
+        state.initialize();
+        state.run_frame();
+
+        let received = state.world.read_resource::<Received>().unwrap();
+        assert_eq!(
+            received.pinch,
+            [PinchGesture {
+                delta: 0.5,
+                phase: TouchPhase::Moved
+            }]
+        );
+        assert_eq!(
+            received.pan,
+            [PanGesture {
+                delta: Vec2::new(3., -4.),
+                phase: TouchPhase::Started
+            }]
+        );
+    }
+}
@@ -0,0 +1,481 @@
+use std::any::TypeId;
+
+use egui::{CollapsingHeader, DragValue, Grid, ScrollArea, Ui};
+use glam::{Vec2, Vec3, Vec4};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    App, Plugin,
+    ecs::{
+        component::{Component, ComponentId},
+        entity::Entity,
+        events::EventRegistry,
+        reflect::Reflect,
+        rendering::{Sprite, Transform},
+        scheduler::Scheduler,
+        world::World,
+    },
+    ui::{Egui, EguiPlugin},
+};
+
+/// A debug window listing every entity with its components, the resources, the registered
+/// events and how long each system took, toggled with [`Inspector::toggle_key`]
+///
+/// Components registered with [`InspectorPlugin::register`] or with reflection (see
+/// [`App::register_type`]) can be edited in the window, only available with the `inspector`
+/// feature
+#[derive(Default)]
+pub struct InspectorPlugin {
+    editors: Vec<(TypeId, EditFn)>,
+}
+
+impl InspectorPlugin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the fields of a component editable in the inspector
+    pub fn register<T: Component + Inspect>(mut self) -> Self {
+        self.editors.push((TypeId::of::<T>(), edit_component::<T>));
+        self
+    }
+}
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, mut app: App) -> App {
+        if app.world().read_resource::<Egui>().is_err() {
+            app = app.add_plugin(EguiPlugin);
+        }
+
+        let mut editors = self.editors.clone();
+        editors.push((TypeId::of::<Transform>(), edit_component::<Transform>));
+        editors.push((TypeId::of::<Sprite>(), edit_component::<Sprite>));
+
+        app.insert_resource(Inspector {
+            open: false,
+            toggle_key: egui::Key::F12,
+            editors,
+        });
+        app
+    }
+}
+
+/// A value that can be edited in the inspector
+pub trait Inspect {
+    fn inspect(&mut self, ui: &mut Ui);
+}
+
+type EditFn = fn(&mut World, Entity, &mut Ui);
+
+fn edit_component<T: Component + Inspect>(world: &mut World, entity: Entity, ui: &mut Ui) {
+    if let Some(component) = world.get_component_mut::<T>(entity) {
+        component.inspect(ui);
+    }
+}
+
+/// The state of the inspector window, added by the [`InspectorPlugin`]
+pub struct Inspector {
+    pub open: bool,
+    pub toggle_key: egui::Key,
+    editors: Vec<(TypeId, EditFn)>,
+}
+
+/// Draws the inspector window into the egui pass of the frame
+///
+/// Runs outside of the scheduler since it needs the whole world, so it shows the state left by
+/// the previous frame
+pub(crate) fn show_inspector(world: &mut World, scheduler: &Scheduler) {
+    let Ok(egui) = world.read_resource::<Egui>() else {
+        return;
+    };
+    let context = egui.context.clone();
+    drop(egui);
+
+    let Ok(mut inspector) = world.write_resource::<Inspector>() else {
+        return;
+    };
+    if context.input(|input| input.key_pressed(inspector.toggle_key)) {
+        inspector.open = !inspector.open;
+    }
+    let mut open = inspector.open;
+    let editors: FxHashMap<ComponentId, EditFn> = inspector
+        .editors
+        .iter()
+        .filter_map(|(type_id, edit)| {
+            let info = world.components_info.get_by_type_id(*type_id)?;
+            Some((info.id(), *edit))
+        })
+        .collect();
+    drop(inspector);
+
+    if !open {
+        return;
+    }
+
+    egui::Window::new("Inspector")
+        .open(&mut open)
+        .default_width(320.)
+        .show(&context, |ui| {
+            ScrollArea::vertical().show(ui, |ui| {
+                entities_ui(world, &editors, ui);
+                resources_ui(world, ui);
+                events_ui(world, ui);
+                systems_ui(scheduler, ui);
+            });
+        });
+
+    world.write_resource::<Inspector>().unwrap().open = open;
+}
+
+fn entities_ui(world: &mut World, editors: &FxHashMap<ComponentId, EditFn>, ui: &mut Ui) {
+    let entities = world.entities().to_vec();
+
+    CollapsingHeader::new(format!("Entities ({})", entities.len())).show(ui, |ui| {
+        for entity in entities {
+            let components: Vec<_> = world
+                .components_info
+                .iter()
+                .filter(|info| world.components.has_component(info.id(), entity))
+                .map(|info| {
+                    let reflect = info
+                        .type_id()
+                        .and_then(|type_id| world.type_registry().get(type_id))
+                        .and_then(|registration| registration.reflect().cloned());
+                    (info.id(), info.name().to_string(), reflect)
+                })
+                .collect();
+
+            CollapsingHeader::new(format!("Entity {}", entity.index()))
+                .id_salt(entity)
+                .show(ui, |ui| {
+                    for (component_id, name, reflect) in components {
+                        // Salts the ids of the editor widgets, which are the same for every
+                        // entity
+                        ui.push_id((entity, component_id), |ui| {
+                            let header = CollapsingHeader::new(short_name(&name));
+
+                            if let Some(edit) = editors.get(&component_id) {
+                                header.show(ui, |ui| edit(world, entity, ui));
+                            } else if let Some(reflect) = reflect {
+                                header.show(ui, |ui| {
+                                    if let Some(component) = reflect.component_mut(world, entity) {
+                                        reflect_ui(component, ui);
+                                    }
+                                });
+                            } else {
+                                ui.label(short_name(&name));
+                            }
+                        });
+                    }
+                });
+        }
+    });
+}
+
+fn resources_ui(world: &World, ui: &mut Ui) {
+    let mut names: Vec<_> = world.resource_names().map(short_name).collect();
+    names.sort_unstable();
+
+    CollapsingHeader::new(format!("Resources ({})", names.len())).show(ui, |ui| {
+        for name in names {
+            ui.label(name);
+        }
+    });
+}
+
+fn events_ui(world: &World, ui: &mut Ui) {
+    let Ok(registry) = world.read_resource::<EventRegistry>() else {
+        return;
+    };
+    let events = registry.pending(world);
+
+    CollapsingHeader::new(format!("Events ({})", events.len())).show(ui, |ui| {
+        Grid::new("inspector events").striped(true).show(ui, |ui| {
+            for (name, pending) in events {
+                ui.label(short_name(name));
+                ui.label(pending.to_string());
+                ui.end_row();
+            }
+        });
+    });
+}
+
+fn systems_ui(scheduler: &Scheduler, ui: &mut Ui) {
+    CollapsingHeader::new("Systems").show(ui, |ui| {
+        Grid::new("inspector systems").striped(true).show(ui, |ui| {
+            for (name, timing) in scheduler.systems() {
+                ui.label(short_name(name));
+                ui.label(format!("{:.3} ms", timing.as_secs_f64() * 1000.));
+                ui.end_row();
+            }
+        });
+    });
+}
+
+/// Edits the plain values inside a reflected value, other values are only named
+fn reflect_ui(value: &mut dyn Reflect, ui: &mut Ui) {
+    if let Some(value) = value.downcast_mut::<f32>() {
+        value.inspect(ui);
+    } else if let Some(value) = value.downcast_mut::<bool>() {
+        value.inspect(ui);
+    } else if let Some(value) = value.downcast_mut::<String>() {
+        value.inspect(ui);
+    } else if let Some(value) = value.downcast_mut::<i32>() {
+        ui.add(DragValue::new(value));
+    } else if let Some(value) = value.downcast_mut::<u32>() {
+        ui.add(DragValue::new(value));
+    } else if value.field_names().is_empty() {
+        ui.label(short_name(value.type_name()));
+    } else {
+        Grid::new(ui.id().with(value.type_name())).show(ui, |ui| {
+            for name in value.field_names() {
+                ui.label(*name);
+                if let Some(field) = value.field_mut(name) {
+                    ui.push_id(*name, |ui| reflect_ui(field, ui));
+                }
+                ui.end_row();
+            }
+        });
+    }
+}
+
+/// Strips the module paths from a type name, `alloc::vec::Vec<dahhan::Foo>` becomes `Vec<Foo>`
+fn short_name(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    let mut segment_start = 0;
+
+    for (index, c) in name.char_indices() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            continue;
+        }
+
+        short.push_str(last_segment(&name[segment_start..index]));
+        short.push(c);
+        segment_start = index + c.len_utf8();
+    }
+    short.push_str(last_segment(&name[segment_start..]));
+
+    short
+}
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+impl Inspect for f32 {
+    fn inspect(&mut self, ui: &mut Ui) {
+        ui.add(DragValue::new(self).speed(0.1));
+    }
+}
+
+impl Inspect for bool {
+    fn inspect(&mut self, ui: &mut Ui) {
+        ui.checkbox(self, "");
+    }
+}
+
+impl Inspect for String {
+    fn inspect(&mut self, ui: &mut Ui) {
+        ui.text_edit_singleline(self);
+    }
+}
+
+impl Inspect for Vec2 {
+    fn inspect(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            self.x.inspect(ui);
+            self.y.inspect(ui);
+        });
+    }
+}
+
+impl Inspect for Vec3 {
+    fn inspect(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            self.x.inspect(ui);
+            self.y.inspect(ui);
+            self.z.inspect(ui);
+        });
+    }
+}
+
+impl Inspect for Vec4 {
+    fn inspect(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            self.x.inspect(ui);
+            self.y.inspect(ui);
+            self.z.inspect(ui);
+            self.w.inspect(ui);
+        });
+    }
+}
+
+impl Inspect for Transform {
+    fn inspect(&mut self, ui: &mut Ui) {
+        Grid::new(ui.id().with("transform")).show(ui, |ui| {
+            ui.label("position");
+            self.position.inspect(ui);
+            ui.end_row();
+            ui.label("rotation");
+            ui.drag_angle(&mut self.rotation);
+            ui.end_row();
+            ui.label("scale");
+            self.scale.inspect(ui);
+            ui.end_row();
+        });
+    }
+}
+
+impl Inspect for Sprite {
+    fn inspect(&mut self, ui: &mut Ui) {
+        Grid::new(ui.id().with("sprite")).show(ui, |ui| {
+            ui.label("size");
+            self.size.inspect(ui);
+            ui.end_row();
+            ui.label("color");
+            let mut color = self.color.to_array();
+            ui.color_edit_button_rgba_unmultiplied(&mut color);
+            self.color = Vec4::from_array(color);
+            ui.end_row();
+            ui.label("flip x");
+            self.flip_x.inspect(ui);
+            ui.end_row();
+            ui.label("flip y");
+            self.flip_y.inspect(ui);
+            ui.end_row();
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input::replay::{InputRecording, RecordedFrame};
+
+    use super::*;
+
+    fn noop() {}
+
+    #[test]
+    fn test_inspector_without_renderer() {
+        let mut recording = InputRecording::new();
+        recording.push_frame(RecordedFrame {
+            frame: 1,
+            delta: 0.1,
+            events: vec![],
+        });
+
+        let mut app = App::new()
+            .add_plugin(InspectorPlugin::new())
+            .add_system(noop);
+        app.add_entity(Transform {
+            position: Vec3::ZERO,
+            rotation: 0.,
+            scale: Vec2::ONE,
+        });
+        app.world_mut().write_resource::<Inspector>().unwrap().open = true;
+        app.replay(&recording);
+        app.replay(&recording);
+
+        assert!(app.world().read_resource::<Inspector>().unwrap().open);
+        let state = &app.state;
+        let (name, _) = state.scheduler.systems().last().unwrap();
+        assert_eq!(short_name(name), "noop");
+    }
+
+    fn key_press(key: egui::Key) -> egui::Event {
+        egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: egui::Modifiers::NONE,
+        }
+    }
+
+    /// Runs the inspector in an egui pass with the given input events
+    fn inspector_pass(app: &mut App, events: Vec<egui::Event>) {
+        let input = egui::RawInput {
+            events,
+            ..Default::default()
+        };
+        app.world_mut()
+            .write_resource::<Egui>()
+            .unwrap()
+            .begin_pass(input);
+        show_inspector(&mut app.state.world, &app.state.scheduler);
+        let output = app.world_mut().write_resource::<Egui>().unwrap().end_pass();
+        output.unwrap().drop_without_applying_deltas();
+    }
+
+    #[test]
+    fn test_inspector_toggle_key() {
+        let mut app = App::new().add_plugin(InspectorPlugin::new());
+
+        inspector_pass(&mut app, vec![key_press(egui::Key::F12)]);
+        assert!(app.world().read_resource::<Inspector>().unwrap().open);
+
+        inspector_pass(&mut app, vec![]);
+        assert!(app.world().read_resource::<Inspector>().unwrap().open);
+
+        inspector_pass(&mut app, vec![key_press(egui::Key::F12)]);
+        assert!(!app.world().read_resource::<Inspector>().unwrap().open);
+    }
+
+    #[derive(Reflect, Default)]
+    struct Visibility {
+        visible: bool,
+    }
+
+    impl Component for Visibility {}
+
+    #[test]
+    fn test_reflect_ui_edits_component() {
+        let mut world = World::new();
+        world.register_component::<Visibility>();
+        let entity = world.add_entity((Visibility::default(),));
+
+        let context = egui::Context::default();
+        let mut run = |events| {
+            let input = egui::RawInput {
+                events,
+                ..Default::default()
+            };
+            let mut rect = egui::Rect::NOTHING;
+            context
+                .run_ui(input, |ui| {
+                    let component = world.get_component_mut::<Visibility>(entity).unwrap();
+                    reflect_ui(component, ui);
+                    rect = ui.min_rect();
+                })
+                .drop_without_applying_deltas();
+            rect
+        };
+
+        // Clicks the checkbox at the end of the only row
+        let rect = run(vec![]);
+        let checkbox = egui::pos2(rect.right() - 4., rect.center().y);
+        let button = |pressed| egui::Event::PointerButton {
+            pos: checkbox,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::NONE,
+        };
+        run(vec![egui::Event::PointerMoved(checkbox), button(true)]);
+        run(vec![button(false)]);
+
+        assert!(world.get_component::<Visibility>(entity).unwrap().visible);
+    }
+
+    #[test]
+    fn test_short_name() {
+        assert_eq!(short_name("dahhan::ecs::rendering::Sprite"), "Sprite");
+        assert_eq!(
+            short_name("dahhan::ecs::events::Events<dahhan::window::WindowResized>"),
+            "Events<WindowResized>"
+        );
+        assert_eq!(
+            short_name("(alloc::string::String, core::option::Option<f32>)"),
+            "(String, Option<f32>)"
+        );
+    }
+}
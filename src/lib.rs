@@ -1,77 +1,175 @@
-use camera::{Camera, update_camera_uniform};
+use animation::{AnimationFinished, SpriteAnimation};
+use atlas::{AtlasSprite, TextureAtlasLayouts};
+use camera::{Camera, RenderTarget, update_camera_uniform};
 use ecs::{
     Component,
     component::TupleAddComponent,
     default_systems::{
-        draw, render_sprites, resize_camera, resize_surface, update_window_resource,
+        animate_sprites, draw, exit_on_close_requested, prepare_textures, propagate_transforms,
+        render_sprites, render_text, resize_camera, resize_surface, update_atlas_sprites,
     },
     entity::Entity,
-    events::EventRegistry,
-    rendering::{Sprite, Transform},
+    events::{EventRegistry, Events},
+    hierarchy::{Children, Parent},
+    reflect::Reflect,
+    rendering::{GlobalTransform, Sprite, Transform, insert_global_transform},
     scheduler::{IntoSystem, Scheduler, System},
     world::World,
 };
-use input::Input;
+use glam::{IVec2, Vec2};
+use input::{
+    Input, InputEvent,
+    replay::{InputRecorder, InputRecording},
+    text::{Ime, ImeSettings, TextInput},
+    touch::{PanGesture, PinchGesture, Touches},
+};
 use prelude::Event;
-use renderer::Renderer;
-use std::{sync::Arc, time::Instant};
+use renderer::{RenderSettings, Renderer};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use text::{Fonts, Text2d};
+use texture::Images;
 use time::Time;
+use ui::{Egui, end_egui_pass, forward_to_game};
+use window::{
+    CloseRequested, ScaleFactorChanged, WindowDescriptor, WindowFocused, WindowMoved, WindowRef,
+};
 use winit::{
-    event::WindowEvent,
+    event::{DeviceEvent, DeviceId, WindowEvent},
     event_loop::{ActiveEventLoop, EventLoop},
-    keyboard::{KeyCode, PhysicalKey},
     window::{Window, WindowId},
 };
 
+// Lets `#[derive(Reflect)]` refer to `::dahhan` inside this crate too
+extern crate self as dahhan;
+
+pub mod animation;
 mod anymap;
+pub mod atlas;
 mod buffers;
 pub mod camera;
 pub mod ecs;
 mod egui_renderer;
 pub mod input;
+#[cfg(feature = "inspector")]
+pub mod inspector;
 pub mod renderer;
+pub mod scene;
+pub mod text;
+pub mod texture;
 pub mod time;
+pub mod ui;
 mod vertices;
 pub mod window;
 
 pub mod prelude {
     pub use crate::{
-        App,
+        App, AppExit, AppResumed, AppSuspended,
+        animation::{AnimationFinished, AnimationMode, SpriteAnimation},
+        atlas::{
+            AtlasLayoutHandle, AtlasSprite, TextureAtlasBuilder, TextureAtlasLayout,
+            TextureAtlasLayouts,
+        },
+        camera::RenderTarget,
         ecs::{
             events::{Event, EventReader, EventWriter},
+            hierarchy::{Children, Parent},
             query::{Query, Read, Write},
-            rendering::{Sprite, Transform},
-            scheduler::{Local, Res, ResMut, Scheduler},
+            reflect::Reflect,
+            removal::RemovedComponents,
+            rendering::{AlphaMode, Anchor, GlobalTransform, Rect, Sprite, Transform},
+            scheduler::{Local, NonSend, NonSendMut, Res, ResMut, Scheduler},
+        },
+        input::{
+            Input,
+            action::{ActionMap, Actions, AxisBinding, Binding, Modifier},
+            keyboard::KeyCode,
+            mouse::MouseButton,
+            text::{Ime, ImeSettings, TextInput},
+            touch::{PanGesture, PinchGesture, Touches},
         },
-        input::{Input, keyboard::KeyCode},
+        renderer::RenderSettings,
+        scene::DynamicScene,
+        text::{FontHandle, Fonts, Text2d, TextAlign, TextSection, TextSpace},
+        texture::{Image, Images, TextureHandle},
         time::Time,
-        window::Window,
+        ui::{EguiContext, EguiPlugin},
+        window::{
+            CloseRequested, CursorGrabMode, PresentMode, ScaleFactorChanged, Window,
+            WindowDescriptor, WindowFocused, WindowMode, WindowMoved, WindowRef,
+        },
     };
+
+    #[cfg(feature = "inspector")]
+    pub use crate::inspector::{Inspect, Inspector, InspectorPlugin};
 }
 
 pub struct App {
     state: State,
-    event_loop: EventLoop<()>,
 }
 
 impl App {
     pub fn new() -> Self {
-        let event_loop = EventLoop::new().unwrap();
-        event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
-
         let state = State::new();
 
-        Self { event_loop, state }
+        Self { state }
     }
 
     pub fn run(mut self) -> Result<(), winit::error::EventLoopError> {
-        self.event_loop.run_app(&mut self.state)
+        let event_loop = EventLoop::new()?;
+        event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
+
+        event_loop.run_app(&mut self.state)
+    }
+
+    /// Runs the app headlessly, feeding it the recorded input and frame deltas instead of winit
+    /// events, so the world evolves the same way it did when it was recorded
+    pub fn replay(&mut self, recording: &InputRecording) {
+        self.state.initialize();
+        self.state
+            .scheduler
+            .startup(self.state.world.as_unsafe_world_cell());
+
+        for frame in recording.frames() {
+            for event in &frame.events {
+                self.state.handle_input_event(event.clone());
+            }
+
+            self.state
+                .world
+                .write_resource::<Time>()
+                .unwrap()
+                .advance(frame.delta);
+
+            if self.state.run_frame() {
+                break;
+            }
+        }
+    }
+
+    /// Records every input event consumed by the app, the recording is saved to `path` on exit
+    pub fn record_input(mut self, path: impl Into<PathBuf>) -> Self {
+        self.state.recorder = Some(InputRecorder::new(path.into()));
+
+        self
+    }
+
+    pub fn world(&self) -> &World {
+        &self.state.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.state.world
     }
 
     pub fn insert_resource<T: 'static>(&mut self, resource: T) {
         self.state.world.insert_resource(resource);
     }
 
+    /// Inserts a resource that isn't `Send`, see [`World::insert_non_send_resource`]
+    pub fn insert_non_send_resource<T: 'static>(&mut self, resource: T) {
+        self.state.world.insert_non_send_resource(resource);
+    }
+
     pub fn register_component<T: Component>(mut self) -> Self {
         self.state.world.register_component::<T>();
 
@@ -113,13 +211,38 @@ impl App {
 
         self
     }
+
+    /// Registers the reflection data of a component or resource type, see [`World::register_type`]
+    pub fn register_type<T: Reflect>(mut self) -> Self {
+        self.state.world.register_type::<T>();
+
+        self
+    }
+
+    pub fn add_plugin(self, plugin: impl Plugin) -> Self {
+        plugin.build(self)
+    }
+}
+
+/// A group of resources, events and systems added to an [`App`] at once
+pub trait Plugin {
+    fn build(&self, app: App) -> App;
+}
+
+struct WindowState {
+    window: Arc<Window>,
+    target: WindowRef,
+    /// The [`window::Window`] as it was last applied to the window
+    applied: window::Window,
 }
 
 struct State {
-    window: Option<Arc<Window>>,
-    window_id: Option<WindowId>,
+    windows: HashMap<WindowId, WindowState>,
+    primary_window_id: Option<WindowId>,
     world: World,
     scheduler: Scheduler,
+    recorder: Option<InputRecorder>,
+    applied_ime_settings: Option<ImeSettings>,
 }
 
 impl State {
@@ -128,8 +251,23 @@ impl State {
         let mut scheduler = Scheduler::new();
 
         world.register_component::<Transform>();
+        world.register_component::<GlobalTransform>();
+        world
+            .register_component_hooks::<Transform>()
+            .on_add(insert_global_transform);
+        world.register_component::<Parent>();
+        world.register_component::<Children>();
         world.register_component::<Sprite>();
+        world.register_type::<Transform>();
+        world.register_type::<Sprite>();
+        world.register_type::<Parent>();
+        world.register_type::<Children>();
         world.register_component::<Camera>();
+        world.register_component::<RenderTarget>();
+        world.register_component::<window::Window>();
+        world.register_component::<AtlasSprite>();
+        world.register_component::<SpriteAnimation>();
+        world.register_component::<Text2d>();
 
         world.insert_resource(Input::new());
 
@@ -137,160 +275,755 @@ impl State {
 
         world.insert_resource(Time::new());
 
+        world.insert_resource(Images::new());
+        world.insert_resource(TextureAtlasLayouts::new());
+        world.insert_resource(Fonts::new());
+        world.insert_resource(RenderSettings::default());
+
         world.add_event::<WindowResized>();
+        world.add_event::<TextInput>();
+        world.add_event::<Ime>();
+        world.insert_resource(ImeSettings::new());
+        world.add_event::<PinchGesture>();
+        world.add_event::<PanGesture>();
+        world.insert_resource(Touches::new());
+        world.add_event::<WindowMoved>();
+        world.add_event::<WindowFocused>();
+        world.add_event::<ScaleFactorChanged>();
+        world.add_event::<CloseRequested>();
+        world.add_event::<AppExit>();
+        world.add_event::<AppSuspended>();
+        world.add_event::<AppResumed>();
+        world.add_event::<AnimationFinished>();
+        world.insert_resource(WindowDescriptor::default());
         world.insert_resource(window::Window::new());
-        scheduler.add_system(update_window_resource);
+        scheduler.add_system(exit_on_close_requested);
+        scheduler.add_system(animate_sprites);
 
         Self {
-            window: None,
-            window_id: None,
+            windows: HashMap::new(),
+            primary_window_id: None,
             world,
             scheduler,
+            recorder: None,
+            applied_ime_settings: None,
         }
     }
 
     pub(crate) fn init_rendering(&mut self, renderer: Renderer) {
         self.world.insert_resource(renderer);
+        self.world
+            .write_resource::<Fonts>()
+            .unwrap()
+            .load_system_fonts();
 
+        self.scheduler.add_system(propagate_transforms);
+        self.scheduler.add_system(update_atlas_sprites);
+        self.scheduler.add_system(prepare_textures);
         self.scheduler.add_system(render_sprites);
-        self.scheduler.add_system(draw);
         self.scheduler.add_system(resize_surface);
         self.scheduler.add_system(resize_camera);
         self.scheduler.add_system(update_camera_uniform);
+        self.scheduler.add_system(render_text);
+
+        if let Ok(egui) = self.world.read_resource::<Egui>() {
+            let context = egui.context.clone();
+            drop(egui);
+
+            self.world
+                .write_resource::<Renderer>()
+                .unwrap()
+                .init_egui(context);
+            self.scheduler.add_system(end_egui_pass);
+        }
+
+        self.scheduler.add_system(draw);
     }
 
     fn initialize(&mut self) {
         self.scheduler.initialize(&mut self.world);
     }
-}
-
-impl winit::application::ApplicationHandler for State {
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
-
-        let window_attributes = Window::default_attributes()
-            .with_title("Dahhan")
-            .with_inner_size(winit::dpi::LogicalSize::new(128.0, 128.0));
-        let window = event_loop.create_window(window_attributes).unwrap();
-        let window = Arc::new(window);
-
-        // FIXME: systems should be added in a better place
-        // because `resumed` could be called more than once
-        let renderer = Renderer::new(Arc::clone(&window));
-        self.init_rendering(renderer);
-
-        self.world.send_event(WindowResized {
-            width: window.inner_size().width as f32,
-            height: window.inner_size().height as f32,
-        });
 
-        self.window_id = Some(window.id());
-        self.window = Some(window);
+    /// Applies an input event to the world, recording it if input recording is enabled
+    fn handle_input_event(&mut self, event: InputEvent) {
+        match event {
+            InputEvent::Resized {
+                window,
+                width,
+                height,
+            } => {
+                self.update_window(window, |window| {
+                    window.width = width;
+                    window.height = height;
+                });
+                self.world.send_event(WindowResized {
+                    window,
+                    width,
+                    height,
+                });
+            }
+            InputEvent::Moved { window, x, y } => {
+                let position = IVec2::new(x, y);
+                self.update_window(window, |window| window.position = Some(position));
+                self.world.send_event(WindowMoved { window, position });
+            }
+            InputEvent::Focused { window, focused } => {
+                self.update_window(window, |window| window.focused = focused);
+                self.world.send_event(WindowFocused { window, focused });
+            }
+            InputEvent::CloseRequested { window } => {
+                // Windows other than the primary one close right away, the window itself is
+                // destroyed at the end of the frame
+                if let WindowRef::Entity(entity) = window {
+                    self.world.remove_component::<window::Window>(entity);
+                }
+                self.world.send_event(CloseRequested { window });
+            }
+            InputEvent::Text { ref text } => {
+                self.world.send_event(TextInput { text: text.clone() });
+            }
+            InputEvent::Ime(ref ime) => {
+                self.world.send_event(ime.clone());
+            }
+            InputEvent::Touch { id, x, y, phase } => {
+                let mut touches = self.world.write_resource::<Touches>().unwrap();
+                touches.handle_touch(id, Vec2::new(x, y), phase);
+            }
+            InputEvent::PinchGesture { delta, phase } => {
+                self.world.send_event(PinchGesture { delta, phase });
+            }
+            InputEvent::PanGesture { x, y, phase } => {
+                self.world.send_event(PanGesture {
+                    delta: Vec2::new(x, y),
+                    phase,
+                });
+            }
+            InputEvent::ScaleFactorChanged {
+                window,
+                scale_factor,
+            } => {
+                self.update_window(window, |window| window.scale_factor = scale_factor);
+                self.world.send_event(ScaleFactorChanged {
+                    window,
+                    scale_factor,
+                });
+            }
+            _ => {}
+        }
 
-        self.initialize();
+        {
+            let mut input = self.world.write_resource::<Input>().unwrap();
+            input.handle_event(&event);
+        }
 
-        self.scheduler.startup(self.world.as_unsafe_world_cell());
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(event);
+        }
     }
 
-    fn window_event(
-        &mut self,
-        event_loop: &winit::event_loop::ActiveEventLoop,
-        _window_id: winit::window::WindowId,
-        event: WindowEvent,
-    ) {
-        let Some(window) = self.window.as_ref() else {
-            return;
-        };
-
-        window.request_redraw();
-
-        // let event_res = {
-        //     let mut renderer = self.world.write_resource::<Renderer>().unwrap();
-        //     renderer.handle_egui_event(&event)
-        // };
+    /// Updates the window resource or component with a change that came from the window itself,
+    /// so it isn't applied back to the window
+    fn update_window(&mut self, target: WindowRef, update: impl Fn(&mut window::Window)) {
+        match target {
+            WindowRef::Primary => {
+                update(&mut self.world.write_resource::<window::Window>().unwrap());
+            }
+            WindowRef::Entity(entity) => {
+                if let Some(window) = self.world.get_component_mut::<window::Window>(entity) {
+                    update(window);
+                }
+            }
+        }
 
-        // if !event_res.consumed {
+        if let Some(window_state) = self
+            .windows
+            .values_mut()
+            .find(|window_state| window_state.target == target)
+        {
+            update(&mut window_state.applied);
+        }
+    }
 
+    /// Converts winit window events to [`InputEvent`]s, events that aren't input are ignored
+    fn handle_window_input(&mut self, window: WindowRef, event: WindowEvent) {
         match event {
             WindowEvent::Resized(new_size) => {
-                self.world.send_event(WindowResized {
+                self.handle_input_event(InputEvent::Resized {
+                    window,
                     width: new_size.width as f32,
                     height: new_size.height as f32,
                 });
             }
-            WindowEvent::RedrawRequested => {
-                self.scheduler.run(self.world.as_unsafe_world_cell());
-
-                {
-                    let mut input = self.world.write_resource::<Input>().unwrap();
-                    input.scroll_delta = 0.;
-                }
-
-                {
-                    let mut time = self.world.write_resource::<Time>().unwrap();
-                    time.last_frame_time = Instant::now();
-                }
-
-                self.world.update_events();
+            WindowEvent::Moved(position) => {
+                self.handle_input_event(InputEvent::Moved {
+                    window,
+                    x: position.x,
+                    y: position.y,
+                });
+            }
+            WindowEvent::Focused(focused) => {
+                self.handle_input_event(InputEvent::Focused { window, focused });
+            }
+            WindowEvent::CloseRequested => {
+                self.handle_input_event(InputEvent::CloseRequested { window });
             }
-            WindowEvent::CloseRequested => event_loop.exit(),
             WindowEvent::KeyboardInput {
                 device_id: _,
                 event,
                 is_synthetic,
             } => {
                 if !is_synthetic {
-                    let mut input = self.world.write_resource::<Input>().unwrap();
-                    match event.physical_key {
-                        key @ PhysicalKey::Code(KeyCode::AltLeft)
-                        | key @ PhysicalKey::Code(KeyCode::AltRight)
-                        | key @ PhysicalKey::Code(KeyCode::ControlLeft)
-                        | key @ PhysicalKey::Code(KeyCode::ControlRight)
-                        | key @ PhysicalKey::Code(KeyCode::ShiftLeft)
-                        | key @ PhysicalKey::Code(KeyCode::ShiftRight)
-                        | key @ PhysicalKey::Code(KeyCode::Meta)
-                        | key @ PhysicalKey::Code(KeyCode::SuperLeft)
-                        | key @ PhysicalKey::Code(KeyCode::SuperRight)
-                        | key @ PhysicalKey::Code(KeyCode::Hyper) => {
-                            if event.state.is_pressed() {
-                                input.pressed_modifiers.insert(key);
-                            } else {
-                                input.pressed_modifiers.remove(&key);
-                            }
-                        }
-                        key => {
-                            if event.state.is_pressed() {
-                                input.pressed_keys.insert(key);
-                            } else {
-                                input.pressed_keys.remove(&key);
-                            }
-                        }
+                    self.handle_input_event(InputEvent::Key {
+                        key: event.physical_key,
+                        pressed: event.state.is_pressed(),
+                    });
+
+                    if event.state.is_pressed()
+                        && let Some(text) = event.text
+                    {
+                        self.handle_input_event(InputEvent::Text {
+                            text: text.to_string(),
+                        });
                     }
                 }
             }
+            WindowEvent::Ime(ime) => {
+                self.handle_input_event(InputEvent::Ime(ime.into()));
+            }
             WindowEvent::MouseWheel {
                 device_id: _,
                 delta,
                 phase: _,
             } => {
-                let mut input = self.world.write_resource::<Input>().unwrap();
-                input.scroll_delta = match delta {
+                let delta = match delta {
                     winit::event::MouseScrollDelta::LineDelta(_, lines) => lines,
                     winit::event::MouseScrollDelta::PixelDelta(physical_position) => {
                         physical_position.y as f32
                     }
                 };
+                self.handle_input_event(InputEvent::MouseWheel { delta });
+            }
+            WindowEvent::CursorMoved {
+                device_id: _,
+                position,
+            } => {
+                self.handle_input_event(InputEvent::CursorMoved {
+                    window,
+                    x: position.x as f32,
+                    y: position.y as f32,
+                });
+            }
+            WindowEvent::CursorEntered { device_id: _ } => {
+                self.handle_input_event(InputEvent::CursorEntered { window });
+            }
+            WindowEvent::CursorLeft { device_id: _ } => {
+                self.handle_input_event(InputEvent::CursorLeft { window });
+            }
+            WindowEvent::MouseInput {
+                device_id: _,
+                state,
+                button,
+            } => {
+                self.handle_input_event(InputEvent::MouseButton {
+                    button,
+                    pressed: state.is_pressed(),
+                });
+            }
+            WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                inner_size_writer: _,
+            } => {
+                self.handle_input_event(InputEvent::ScaleFactorChanged {
+                    window,
+                    scale_factor,
+                });
+            }
+            WindowEvent::Touch(touch) => {
+                self.handle_input_event(InputEvent::from(touch));
+            }
+            WindowEvent::PinchGesture {
+                device_id: _,
+                delta,
+                phase,
+            } => {
+                self.handle_input_event(InputEvent::PinchGesture {
+                    delta: delta as f32,
+                    phase,
+                });
+            }
+            WindowEvent::PanGesture {
+                device_id: _,
+                delta,
+                phase,
+            } => {
+                self.handle_input_event(InputEvent::PanGesture {
+                    x: delta.x,
+                    y: delta.y,
+                    phase,
+                });
             }
             _ => {}
         };
-        // }
+    }
+
+    /// Runs all the systems once, [`Time`] must already be advanced to the new frame
+    ///
+    /// Returns `true` if a system requested to exit the app
+    fn run_frame(&mut self) -> bool {
+        if let Some(recorder) = self.recorder.as_mut() {
+            let time = self.world.read_resource::<Time>().unwrap();
+            recorder.start_frame(time.frame_count(), time.delta_time());
+        }
+
+        if let Ok(mut egui) = self.world.write_resource::<Egui>() {
+            let input = self
+                .world
+                .write_resource::<Renderer>()
+                .ok()
+                .and_then(|mut renderer| renderer.take_egui_input())
+                .unwrap_or_default();
+            egui.begin_pass(input);
+        }
+
+        #[cfg(feature = "inspector")]
+        inspector::show_inspector(&mut self.world, &self.scheduler);
+
+        self.scheduler.run(self.world.as_unsafe_world_cell());
+
+        // Without a renderer nothing ends the pass
+        if let Ok(mut egui) = self.world.write_resource::<Egui>()
+            && let Some(output) = egui.end_pass()
+        {
+            output.drop_without_applying_deltas();
+        }
+
+        {
+            let mut input = self.world.write_resource::<Input>().unwrap();
+            input.end_frame();
+        }
+
+        {
+            let mut touches = self.world.write_resource::<Touches>().unwrap();
+            touches.end_frame();
+        }
+
+        let exit = !self
+            .world
+            .read_resource::<Events<AppExit>>()
+            .unwrap()
+            .is_empty();
+
+        self.world.update_events();
+
+        exit
+    }
+
+    /// Drops the window surfaces and runs a frame so systems can react to [`AppSuspended`]
+    /// before the app stops receiving frames
+    ///
+    /// Returns `true` if a system requested to exit the app
+    fn suspend(&mut self) -> bool {
+        if let Ok(mut renderer) = self.world.write_resource::<Renderer>() {
+            renderer.suspend();
+        }
+
+        self.world.send_event(AppSuspended);
+
+        self.world.write_resource::<Time>().unwrap().update();
+        self.run_frame()
+    }
+
+    /// Recreates the surfaces of the existing windows, keeping every other GPU resource
+    fn resume(&mut self) {
+        let windows: Vec<_> = self
+            .windows
+            .values()
+            .map(|window_state| (window_state.target, Arc::clone(&window_state.window)))
+            .collect();
+
+        for (target, window) in windows {
+            if let Ok(mut renderer) = self.world.write_resource::<Renderer>() {
+                renderer.add_window(target, Arc::clone(&window));
+            }
+
+            // The window may have been resized while the app was in the background
+            self.handle_window_created(target, &window);
+        }
+
+        // The time spent in the background isn't part of the next frame
+        self.world.write_resource::<Time>().unwrap().reset_frame();
+
+        self.world.send_event(AppResumed);
+    }
+
+    /// Opens a window for every new [`window::Window`] component, closes the windows whose
+    /// component was removed, and applies changes made by systems to the windows
+    fn sync_windows(&mut self, event_loop: &ActiveEventLoop) {
+        let entities = self
+            .world
+            .as_unsafe_world_cell()
+            .entities::<window::Window>();
+
+        let closed: Vec<_> = self
+            .windows
+            .iter()
+            .filter(|(_, window_state)| match window_state.target {
+                WindowRef::Primary => false,
+                WindowRef::Entity(entity) => !entities.contains(&entity),
+            })
+            .map(|(id, window_state)| (*id, window_state.target))
+            .collect();
+
+        for (id, target) in closed {
+            if let Ok(mut renderer) = self.world.write_resource::<Renderer>() {
+                renderer.remove_window(target);
+            }
+            self.windows.remove(&id);
+        }
+
+        for entity in entities {
+            let target = WindowRef::Entity(entity);
+            if self
+                .windows
+                .values()
+                .any(|window_state| window_state.target == target)
+            {
+                continue;
+            }
+
+            let Some(component) = self.world.get_component::<window::Window>(entity) else {
+                continue;
+            };
+
+            let window = match event_loop
+                .create_window(component.attributes(event_loop.primary_monitor()))
+            {
+                Ok(window) => Arc::new(window),
+                Err(e) => {
+                    tracing::error!("Failed to create window for {entity:?}: {e}");
+                    continue;
+                }
+            };
+
+            self.windows.insert(
+                window.id(),
+                WindowState {
+                    window: Arc::clone(&window),
+                    target,
+                    applied: component.clone(),
+                },
+            );
+
+            if let Ok(mut renderer) = self.world.write_resource::<Renderer>() {
+                renderer.add_window(target, Arc::clone(&window));
+            }
+
+            self.handle_window_created(target, &window);
+        }
+
+        for window_state in self.windows.values_mut() {
+            let current = match window_state.target {
+                WindowRef::Primary => Some(
+                    self.world
+                        .read_resource::<window::Window>()
+                        .unwrap()
+                        .clone(),
+                ),
+                WindowRef::Entity(entity) => {
+                    self.world.get_component::<window::Window>(entity).cloned()
+                }
+            };
+
+            if let Some(current) = current
+                && current != window_state.applied
+            {
+                current.apply_changes(&window_state.applied, &window_state.window);
+                window_state.applied = current;
+            }
+        }
+    }
+
+    /// Sends the initial size, scale factor and position of a newly created window
+    fn handle_window_created(&mut self, target: WindowRef, window: &Window) {
+        self.handle_input_event(InputEvent::Resized {
+            window: target,
+            width: window.inner_size().width as f32,
+            height: window.inner_size().height as f32,
+        });
+
+        self.handle_input_event(InputEvent::ScaleFactorChanged {
+            window: target,
+            scale_factor: window.scale_factor(),
+        });
+
+        if let Ok(position) = window.outer_position() {
+            self.handle_input_event(InputEvent::Moved {
+                window: target,
+                x: position.x,
+                y: position.y,
+            });
+        }
+    }
+
+    /// Applies changes to [`ImeSettings`] made by systems to the window
+    fn sync_ime_settings(&mut self) {
+        let Some(window) = self
+            .primary_window_id
+            .and_then(|id| self.windows.get(&id))
+            .map(|window_state| &window_state.window)
+        else {
+            return;
+        };
+
+        let ime_settings = self.world.read_resource::<ImeSettings>().unwrap();
+        if self.applied_ime_settings.as_ref() != Some(&*ime_settings) {
+            ime_settings.apply(window);
+            self.applied_ime_settings = Some(ime_settings.clone());
+        }
     }
 }
 
+impl winit::application::ApplicationHandler for State {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        event_loop.set_control_flow(winit::event_loop::ControlFlow::Poll);
+
+        if self.world.read_resource::<Renderer>().is_ok() {
+            self.resume();
+            return;
+        }
+
+        let window_descriptor = self
+            .world
+            .read_resource::<WindowDescriptor>()
+            .unwrap()
+            .clone();
+        let window_attributes = window_descriptor.attributes(event_loop.primary_monitor());
+        let window = event_loop.create_window(window_attributes).unwrap();
+        let window = Arc::new(window);
+
+        let depth_buffer = self
+            .world
+            .read_resource::<RenderSettings>()
+            .unwrap()
+            .depth_buffer;
+        let renderer = Renderer::new(
+            Arc::clone(&window),
+            window_descriptor.present_mode,
+            depth_buffer,
+        );
+        self.init_rendering(renderer);
+
+        let window_resource = window::Window::from_descriptor(&window_descriptor);
+        self.world.insert_resource(window_resource.clone());
+
+        self.windows.insert(
+            window.id(),
+            WindowState {
+                window: Arc::clone(&window),
+                target: WindowRef::Primary,
+                applied: window_resource,
+            },
+        );
+        self.primary_window_id = Some(window.id());
+
+        self.handle_window_created(WindowRef::Primary, &window);
+
+        self.initialize();
+
+        self.scheduler.startup(self.world.as_unsafe_world_cell());
+    }
+
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
+        if self.suspend() {
+            event_loop.exit();
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        window_id: winit::window::WindowId,
+        event: WindowEvent,
+    ) {
+        let Some(target) = self
+            .windows
+            .get(&window_id)
+            .map(|window_state| window_state.target)
+        else {
+            return;
+        };
+
+        if let Some(primary_window) = self.primary_window_id.and_then(|id| self.windows.get(&id)) {
+            primary_window.window.request_redraw();
+        }
+
+        if target == WindowRef::Primary
+            && let Ok(mut renderer) = self.world.write_resource::<Renderer>()
+            && let Some(response) = renderer.handle_egui_event(&event)
+            && !forward_to_game(&event, response.consumed)
+        {
+            return;
+        }
+
+        match event {
+            // All windows are drawn in the frame of the primary window
+            WindowEvent::RedrawRequested if target != WindowRef::Primary => {}
+            WindowEvent::RedrawRequested => {
+                self.world.write_resource::<Time>().unwrap().update();
+
+                if self.run_frame() {
+                    event_loop.exit();
+                }
+
+                self.sync_windows(event_loop);
+                self.sync_ime_settings();
+            }
+            event => self.handle_window_input(target, event),
+        };
+    }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _device_id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        if let DeviceEvent::MouseMotion { delta: (x, y) } = event {
+            self.handle_input_event(InputEvent::MouseMotion {
+                x: x as f32,
+                y: y as f32,
+            });
+        }
+    }
+
+    fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(recorder) = self.recorder.as_ref()
+            && let Err(e) = recorder.save()
+        {
+            tracing::error!("Failed to save input recording: {e}");
+        }
+    }
+}
+
+/// Send this event to exit the app at the end of the frame
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AppExit;
+
+impl Event for AppExit {}
+
+/// Sent when the app goes to the background, on mobile the window surfaces are destroyed until
+/// it's resumed
+///
+/// A frame is run right after it's sent, so it's the last chance to save state
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AppSuspended;
+
+impl Event for AppSuspended {}
+
+/// Sent when the app comes back from the background after [`AppSuspended`]
+///
+/// Not sent when the app starts, use startup systems for that
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AppResumed;
+
+impl Event for AppResumed {}
+
 #[derive(Copy, Clone)]
 pub struct WindowResized {
+    pub window: WindowRef,
     pub width: f32,
     pub height: f32,
 }
 
 impl Event for WindowResized {}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use ecs::{events::EventReader, scheduler::ResMut};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Lifecycle {
+        suspended: usize,
+        resumed: usize,
+    }
+
+    fn track_lifecycle(
+        mut suspended: EventReader<AppSuspended>,
+        mut resumed: EventReader<AppResumed>,
+        mut lifecycle: ResMut<Lifecycle>,
+    ) {
+        lifecycle.suspended += suspended.read().count();
+        lifecycle.resumed += resumed.read().count();
+    }
+
+    #[test]
+    fn test_suspend_and_resume() {
+        let mut state = State::new();
+        state.world.insert_resource(Lifecycle::default());
+        state.scheduler.add_system(track_lifecycle);
+        state.initialize();
+
+        assert!(!state.suspend());
+        {
+            let lifecycle = state.world.read_resource::<Lifecycle>().unwrap();
+            assert_eq!((lifecycle.suspended, lifecycle.resumed), (1, 0));
+        }
+
+        state.resume();
+        state.run_frame();
+
+        let lifecycle = state.world.read_resource::<Lifecycle>().unwrap();
+        assert_eq!((lifecycle.suspended, lifecycle.resumed), (1, 1));
+    }
+
+    #[derive(Default)]
+    struct AddedTransforms(usize);
+
+    fn count_added_transform(world: &mut World, _entity: Entity) {
+        world.write_resource::<AddedTransforms>().unwrap().0 += 1;
+    }
+
+    #[test]
+    fn test_transform_on_add_hook() {
+        let mut state = State::new();
+        state.world.insert_resource(AddedTransforms::default());
+        state
+            .world
+            .register_component_hooks::<Transform>()
+            .on_add(count_added_transform);
+
+        let entity = state.world.add_entity((Transform::default(),));
+
+        // The user hook runs next to the one giving the entity its global transform
+        assert_eq!(state.world.read_resource::<AddedTransforms>().unwrap().0, 1);
+        assert!(
+            state
+                .world
+                .get_component::<GlobalTransform>(entity)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_resume_resets_frame_time() {
+        let mut state = State::new();
+        state.initialize();
+
+        assert!(!state.suspend());
+
+        // Pretend the app stayed in the background for a while
+        {
+            let mut time = state.world.write_resource::<Time>().unwrap();
+            time.last_frame_time = Instant::now() - Duration::from_secs(5);
+        }
+
+        state.resume();
+        state.world.write_resource::<Time>().unwrap().update();
+
+        assert!(state.world.read_resource::<Time>().unwrap().delta_time() < 1.);
+    }
+}
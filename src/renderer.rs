@@ -1,17 +1,24 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, ops::Range, sync::Arc};
 
+use glam::{Mat4, Vec2};
 use wgpu::{
-    BindGroup, Buffer, Device, ExperimentalFeatures, PipelineCompilationOptions, Queue,
-    RenderPipeline, Surface, SurfaceConfiguration, util::DeviceExt,
+    Adapter, BindGroup, BindGroupLayout, Buffer, Device, ExperimentalFeatures,
+    PipelineCompilationOptions, Queue, RenderPipeline, Sampler, Surface, SurfaceConfiguration,
+    TextureFormat, util::DeviceExt,
 };
 use winit::{dpi::PhysicalSize, window::Window};
 
 use crate::{
-    buffers::SlicedBuffer, camera::camera_uniform::CameraUniform, vertices::VertexColored,
+    buffers::SlicedBuffer,
+    camera::{RenderTarget, camera_uniform::CameraUniform},
+    ecs::{entity::Entity, rendering::Transform},
+    egui_renderer::EguiRenderer,
+    text::{Fonts, GpuText, Text2d, TextPassId, TextPlacement, TextSpace},
+    texture::{GpuTexture, Image, Images, TextureHandle},
+    vertices::{QuadVertex, SpriteInstance},
+    window::WindowRef,
 };
 
-const INDICES: &[u16] = &[0, 1, 4, 1, 2, 4, 2, 3, 4];
-
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct UniformBuffer {
@@ -28,26 +35,81 @@ impl UniformBuffer {
     }
 }
 
-pub struct Renderer {
+pub(crate) struct WindowSurface {
     pub(crate) surface: Surface<'static>,
     pub(crate) window: Arc<Window>,
     pub(crate) config: SurfaceConfiguration,
+}
+
+pub(crate) const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
+
+/// Renderer options, read when the renderer is created so changing them later has no effect
+#[derive(Debug, Clone, Default)]
+pub struct RenderSettings {
+    /// Adds a depth attachment so opaque sprites hide what's behind them regardless of draw
+    /// order, translucent sprites are still sorted back to front
+    pub depth_buffer: bool,
+}
+
+/// A range of sprite instances drawn with the same texture and pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SpriteBatch {
+    pub(crate) texture: Option<TextureHandle>,
+    pub(crate) translucent: bool,
+    pub(crate) instances: Range<u32>,
+}
+
+/// The uniform buffer of a camera and where it renders to
+pub(crate) struct CameraView {
+    pub(crate) target: RenderTarget,
+    pub(crate) view_proj: Mat4,
+    pub(crate) buffer: Buffer,
+    pub(crate) bind_group: BindGroup,
+}
+
+pub struct Renderer {
+    pub(crate) surfaces: HashMap<WindowRef, WindowSurface>,
+    pub(crate) adapter: Adapter,
     pub(crate) device: Device,
     pub(crate) queue: Queue,
+    /// Draws opaque sprites, writing to the depth buffer if it's enabled
     pub(crate) render_pipeline: RenderPipeline,
-    pub(crate) camera_bind_group: BindGroup,
+    pub(crate) translucent_pipeline: RenderPipeline,
+    pub(crate) depth_buffer: bool,
+    /// Depth textures by size, shared by every target of that size since each camera clears it
+    pub(crate) depth_textures: HashMap<(u32, u32), wgpu::TextureView>,
+    pub(crate) format: TextureFormat,
+    pub(crate) present_mode: wgpu::PresentMode,
+    pub(crate) camera_bind_group_layout: BindGroupLayout,
+    /// One view per camera, filled every frame by
+    /// [`update_camera_uniform`](crate::camera::update_camera_uniform)
+    pub(crate) camera_views: Vec<CameraView>,
+    pub(crate) camera_count: usize,
     pub(crate) uniform_bind_group: BindGroup,
     pub(crate) uniform_buffer: Buffer,
-    pub(crate) vertex_buffer: SlicedBuffer,
-    pub(crate) num_indices: u32,
-    pub(crate) index_buffer: SlicedBuffer,
-    pub(crate) camera_buffer: wgpu::Buffer,
+    pub(crate) texture_bind_group_layout: BindGroupLayout,
+    pub(crate) sampler: Sampler,
+    pub(crate) textures: HashMap<TextureHandle, GpuTexture>,
+    /// Used by sprites without a texture
+    pub(crate) white_texture: GpuTexture,
+    /// The unit quad every sprite instance is drawn with
+    pub(crate) quad_vertex_buffer: Buffer,
+    pub(crate) quad_index_buffer: Buffer,
+    pub(crate) instance_buffer: SlicedBuffer,
+    pub(crate) batches: Vec<SpriteBatch>,
+    pub(crate) text: GpuText,
+    /// Created by the [`EguiPlugin`](crate::ui::EguiPlugin), draws on the primary window
+    pub(crate) egui: Option<EguiRenderer>,
     pub(crate) instance: wgpu::Instance,
-    // camera_uniform: CameraUniform,
 }
 
 impl Renderer {
-    pub(crate) fn new(window: Arc<Window>) -> Self {
+    /// Creates the renderer with a surface for the primary window
+    pub(crate) fn new(
+        window: Arc<Window>,
+        present_mode: wgpu::PresentMode,
+        depth_buffer: bool,
+    ) -> Self {
         let mut size = window.inner_size();
         size.width = size.width.max(1);
         size.height = size.height.max(1);
@@ -78,7 +140,7 @@ impl Renderer {
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: None,
-            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("test-shader.wgsl"))),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("sprite.wgsl"))),
         });
 
         let uniform = UniformBuffer::new(size.width as f32, size.height as f32);
@@ -113,12 +175,6 @@ impl Renderer {
             }],
         });
 
-        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Camera Buffer"),
-            contents: bytemuck::cast_slice(&[CameraUniform::new()]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
-
         let camera_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
                 entries: &[wgpu::BindGroupLayoutEntry {
@@ -134,18 +190,54 @@ impl Renderer {
                 label: Some("camera_bind_group_layout"),
             });
 
-        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &camera_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: camera_buffer.as_entire_binding(),
-            }],
-            label: Some("camera_bind_group"),
+        let texture_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+                label: Some("texture_bind_group_layout"),
+            });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Sprite Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            // Nearest filtering keeps pixel art sharp
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
         });
 
+        let white_texture = GpuTexture::new(
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+            &sampler,
+            &Image::new(1, 1, vec![255; 4]),
+        );
+
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Pipeline layout"),
-            bind_group_layouts: &[Some(&camera_bind_group_layout)],
+            bind_group_layouts: &[
+                Some(&camera_bind_group_layout),
+                Some(&texture_bind_group_layout),
+            ],
             immediate_size: 0,
         });
 
@@ -157,97 +249,170 @@ impl Renderer {
             .copied()
             .unwrap_or(swapchain_capabilities.formats[0]);
 
-        let config = surface
+        let mut config = surface
             .get_default_config(&adapter, size.width, size.height)
             .unwrap();
+        config.present_mode = present_mode;
         surface.configure(&device, &config);
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[Some(VertexColored::desc())],
-                compilation_options: PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(swapchain_format.into())],
-                compilation_options: PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
-                polygon_mode: wgpu::PolygonMode::Fill,
-                // Requires Features::DEPTH_CLIP_CONTROL
-                unclipped_depth: false,
-                // Requires Features::CONSERVATIVE_RASTERIZATION
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            cache: None,
-            multiview_mask: None,
-        });
+        let depth_stencil = |depth_write_enabled| {
+            depth_buffer.then(|| wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: Some(depth_write_enabled),
+                depth_compare: Some(wgpu::CompareFunction::LessEqual),
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            })
+        };
+
+        let render_pipeline = create_sprite_pipeline(
+            &device,
+            &pipeline_layout,
+            &shader,
+            swapchain_format,
+            depth_stencil(true),
+        );
 
-        const VERTEX_BUFFER_START_CAPACITY: wgpu::BufferAddress =
-            (std::mem::size_of::<VertexColored>() * 1024) as _;
-        const INDEX_BUFFER_START_CAPACITY: wgpu::BufferAddress =
-            (std::mem::size_of::<u32>() * 1024 * 3) as _;
+        // Translucent sprites are tested against the depth of opaque ones but don't write to it,
+        // so the sprites behind them are still blended
+        let translucent_pipeline = create_sprite_pipeline(
+            &device,
+            &pipeline_layout,
+            &shader,
+            swapchain_format,
+            depth_stencil(false),
+        );
 
-        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Vertex Buffer"),
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
-            size: VERTEX_BUFFER_START_CAPACITY,
-            mapped_at_creation: false,
+        let quad_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Quad Vertex Buffer"),
+            contents: bytemuck::cast_slice(&QuadVertex::QUAD),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let quad_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Quad Index Buffer"),
+            contents: bytemuck::cast_slice(&QuadVertex::INDICES),
+            usage: wgpu::BufferUsages::INDEX,
         });
 
-        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Index Buffer"),
-            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
-            size: INDEX_BUFFER_START_CAPACITY,
+        // Grows when a frame has more sprites
+        const INSTANCE_BUFFER_START_CAPACITY: wgpu::BufferAddress =
+            (std::mem::size_of::<SpriteInstance>() * 1024) as _;
+
+        let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Instance Buffer"),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            size: INSTANCE_BUFFER_START_CAPACITY,
             mapped_at_creation: false,
         });
 
-        let vertex_buffer_size = vertex_buffer.size();
-        let index_buffer_size = index_buffer.size();
+        // Text is drawn over the sprites of a camera, so it ignores their depth
+        let text = GpuText::new(
+            &device,
+            &queue,
+            swapchain_format,
+            depth_buffer.then(|| wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: Some(false),
+                depth_compare: Some(wgpu::CompareFunction::Always),
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+        );
 
-        let num_indices = INDICES.len() as u32;
+        let mut surfaces = HashMap::new();
+        surfaces.insert(
+            WindowRef::Primary,
+            WindowSurface {
+                surface,
+                window,
+                config,
+            },
+        );
 
         Self {
             instance,
-            surface,
-            config,
+            surfaces,
+            adapter,
             device,
             render_pipeline,
+            translucent_pipeline,
+            depth_buffer,
+            depth_textures: HashMap::new(),
+            format: swapchain_format,
+            present_mode,
             queue,
-            window,
-            index_buffer: SlicedBuffer::new(index_buffer, index_buffer_size),
-            vertex_buffer: SlicedBuffer::new(vertex_buffer, vertex_buffer_size),
-            // camera,
-            // camera_bind_group,
-            // camera_uniform,
-            // camera_buffer,
+            quad_vertex_buffer,
+            quad_index_buffer,
+            instance_buffer: SlicedBuffer::new(instance_buffer, INSTANCE_BUFFER_START_CAPACITY),
             uniform_bind_group,
-            num_indices,
+            texture_bind_group_layout,
+            sampler,
+            textures: HashMap::new(),
+            white_texture,
+            batches: Vec::new(),
+            text,
+            egui: None,
             uniform_buffer,
-            camera_bind_group,
-            camera_buffer,
+            camera_bind_group_layout,
+            camera_views: Vec::new(),
+            camera_count: 0,
         }
     }
 
-    pub(crate) fn resize(&mut self, new_size: PhysicalSize<u32>) {
-        self.config.width = new_size.width.max(1);
-        self.config.height = new_size.height.max(1);
+    /// The format textures used as a [`RenderTarget::Texture`] must have
+    pub fn surface_format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Creates a surface for a window, configured like the primary one
+    ///
+    /// Also used to recreate the surfaces dropped by [`Renderer::suspend`]
+    pub(crate) fn add_window(&mut self, target: WindowRef, window: Arc<Window>) {
+        let size = window.inner_size();
+        let surface = self.instance.create_surface(Arc::clone(&window)).unwrap();
+
+        let mut config = surface
+            .get_default_config(&self.adapter, size.width.max(1), size.height.max(1))
+            .unwrap();
+        config.format = self.format;
+        config.present_mode = self.present_mode;
+        surface.configure(&self.device, &config);
+
+        self.surfaces.insert(
+            target,
+            WindowSurface {
+                surface,
+                window,
+                config,
+            },
+        );
+    }
+
+    pub(crate) fn remove_window(&mut self, target: WindowRef) {
+        self.surfaces.remove(&target);
+    }
+
+    /// Drops all surfaces, the device and every other GPU resource are kept
+    pub(crate) fn suspend(&mut self) {
+        self.surfaces.clear();
+    }
+
+    pub(crate) fn resize(&mut self, target: WindowRef, new_size: PhysicalSize<u32>) {
+        let Some(window_surface) = self.surfaces.get_mut(&target) else {
+            return;
+        };
+
+        window_surface.config.width = new_size.width.max(1);
+        window_surface.config.height = new_size.height.max(1);
+        window_surface
+            .surface
+            .configure(&self.device, &window_surface.config);
+
+        if target != WindowRef::Primary {
+            return;
+        }
+
         let mut writer = self
             .queue
             .write_buffer_with(
@@ -260,27 +425,332 @@ impl Renderer {
             new_size.width as f32,
             new_size.height as f32,
         )]));
+    }
+
+    /// Gets the next texture of a window surface, reconfiguring the surface if it can't be used
+    pub(crate) fn acquire_frame(&mut self, target: WindowRef) -> Option<wgpu::SurfaceTexture> {
+        let window_surface = self.surfaces.get_mut(&target)?;
+
+        match window_surface.surface.get_current_texture() {
+            wgpu::CurrentSurfaceTexture::Success(surface_texture) => Some(surface_texture),
+            wgpu::CurrentSurfaceTexture::Suboptimal(surface_texture) => {
+                drop(surface_texture);
+                window_surface
+                    .surface
+                    .configure(&self.device, &window_surface.config);
+                None
+            }
+            wgpu::CurrentSurfaceTexture::Timeout | wgpu::CurrentSurfaceTexture::Occluded => None,
+            wgpu::CurrentSurfaceTexture::Outdated => {
+                println!("outdated");
+                window_surface
+                    .surface
+                    .configure(&self.device, &window_surface.config);
+                None
+            }
+            wgpu::CurrentSurfaceTexture::Lost => {
+                println!("lost");
+                window_surface.surface = self
+                    .instance
+                    .create_surface(window_surface.window.clone())
+                    .unwrap();
+                window_surface
+                    .surface
+                    .configure(&self.device, &window_surface.config);
+                None
+            }
+            wgpu::CurrentSurfaceTexture::Validation => {
+                unreachable!("validation");
+            }
+        }
+    }
+
+    /// Writes the camera uniform of the next camera rendering into `target`, creating its
+    /// buffer if there are more cameras than last frame
+    pub(crate) fn push_camera(&mut self, target: RenderTarget, camera_uniform: CameraUniform) {
+        if let Some(view) = self.camera_views.get_mut(self.camera_count) {
+            view.target = target;
+            view.view_proj = camera_uniform.view_proj();
+        } else {
+            let buffer = self
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Camera Buffer"),
+                    contents: bytemuck::cast_slice(&[camera_uniform]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                });
+
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.camera_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+                label: Some("camera_bind_group"),
+            });
+
+            self.camera_views.push(CameraView {
+                target,
+                view_proj: camera_uniform.view_proj(),
+                buffer,
+                bind_group,
+            });
+        }
+
+        let view = &self.camera_views[self.camera_count];
+        self.queue
+            .write_buffer(&view.buffer, 0, bytemuck::cast_slice(&[camera_uniform]));
+
+        self.camera_count += 1;
+    }
+
+    /// The cameras updated this frame
+    pub(crate) fn cameras(&self) -> &[CameraView] {
+        &self.camera_views[..self.camera_count]
+    }
+
+    /// Uploads the images that aren't on the GPU yet and drops the ones that were removed
+    pub(crate) fn prepare_textures(&mut self, images: &Images) {
+        self.textures
+            .retain(|handle, _| images.get(*handle).is_some());
+
+        for (handle, image) in images.iter() {
+            if !self.textures.contains_key(&handle) {
+                let texture = GpuTexture::new(
+                    &self.device,
+                    &self.queue,
+                    &self.texture_bind_group_layout,
+                    &self.sampler,
+                    image,
+                );
+                self.textures.insert(handle, texture);
+            }
+        }
+    }
+
+    /// Creates the depth textures for these target sizes, dropping the ones of sizes that
+    /// aren't used anymore (e.g. after a window was resized)
+    pub(crate) fn prepare_depth_textures(&mut self, sizes: &[(u32, u32)]) {
+        if !self.depth_buffer {
+            return;
+        }
+
+        self.depth_textures.retain(|size, _| sizes.contains(size));
+
+        for &size in sizes {
+            if self.depth_textures.contains_key(&size) {
+                continue;
+            }
+
+            let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Depth Texture"),
+                size: wgpu::Extent3d {
+                    width: size.0.max(1),
+                    height: size.1.max(1),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: DEPTH_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            });
+
+            self.depth_textures.insert(
+                size,
+                texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            );
+        }
+    }
+
+    /// The size of the texture a camera renders into
+    pub(crate) fn target_size(&self, target: &RenderTarget) -> Option<(u32, u32)> {
+        match target {
+            RenderTarget::Window(window) => {
+                let config = &self.surfaces.get(window)?.config;
+                Some((config.width, config.height))
+            }
+            RenderTarget::Texture(view) => Some((view.texture().width(), view.texture().height())),
+        }
+    }
 
-        self.surface.configure(&self.device, &self.config);
+    /// The bind group of a texture, falling back to plain white for missing textures
+    pub(crate) fn texture_bind_group(&self, texture: Option<TextureHandle>) -> &BindGroup {
+        &texture
+            .and_then(|handle| self.textures.get(&handle))
+            .unwrap_or(&self.white_texture)
+            .bind_group
     }
 
-    pub fn render_sprites(&mut self, vertices: &[VertexColored], indices: &[u16]) {
-        if vertices.is_empty() || indices.is_empty() {
+    /// Streams the sprite instances of the frame into the instance buffer, growing it if needed
+    pub(crate) fn render_sprites(
+        &mut self,
+        instances: &[SpriteInstance],
+        batches: Vec<SpriteBatch>,
+    ) {
+        let instances: &[u8] = bytemuck::cast_slice(instances);
+
+        self.instance_buffer.clear();
+        self.instance_buffer
+            .reserve(&self.device, instances.len() as wgpu::BufferAddress);
+        self.instance_buffer.push(&self.queue, instances);
+
+        self.batches = batches;
+    }
+
+    pub(crate) fn init_egui(&mut self, context: egui::Context) {
+        let Some(surface) = self.surfaces.get(&WindowRef::Primary) else {
             return;
+        };
+
+        self.egui = Some(EguiRenderer::new(
+            context,
+            &self.device,
+            self.format,
+            None,
+            1,
+            &surface.window,
+        ));
+    }
+
+    /// Lets egui handle an event of the primary window, `None` without egui
+    pub(crate) fn handle_egui_event(
+        &mut self,
+        event: &winit::event::WindowEvent,
+    ) -> Option<egui_winit::EventResponse> {
+        let surface = self.surfaces.get(&WindowRef::Primary)?;
+        let egui = self.egui.as_mut()?;
+
+        Some(egui.handle_input(&surface.window, event))
+    }
+
+    /// The egui input gathered since the previous frame
+    pub(crate) fn take_egui_input(&mut self) -> Option<egui::RawInput> {
+        let surface = self.surfaces.get(&WindowRef::Primary)?;
+        let egui = self.egui.as_mut()?;
+
+        Some(egui.take_input(&surface.window))
+    }
+
+    pub(crate) fn prepare_egui(&mut self, output: egui::FullOutput) {
+        match (self.egui.as_mut(), self.surfaces.get(&WindowRef::Primary)) {
+            (Some(egui), Some(surface)) => egui.prepare(&surface.window, output),
+            _ => output.drop_without_applying_deltas(),
         }
+    }
 
-        // Update the vertex buffer with new data
-        self.queue.write_buffer(
-            &self.vertex_buffer.buffer,
-            0,
-            bytemuck::cast_slice(vertices),
+    /// Shapes the texts and prepares them for every camera and for the screen space of every
+    /// window
+    pub(crate) fn prepare_text(
+        &mut self,
+        fonts: &mut Fonts,
+        texts: &[(Entity, (&Text2d, &Transform))],
+    ) {
+        self.text.update_texts(
+            fonts,
+            texts.iter().map(|(entity, (text, _))| (*entity, *text)),
         );
 
-        // Update the index buffer with new data
-        self.queue
-            .write_buffer(&self.index_buffer.buffer, 0, bytemuck::cast_slice(indices));
+        let windows: Vec<_> = self.surfaces.keys().copied().collect();
+        self.text.begin_frame(&windows);
+
+        for (index, camera) in self.camera_views[..self.camera_count].iter().enumerate() {
+            let Some(size) = self.target_size(&camera.target) else {
+                continue;
+            };
+
+            let viewport_size = Vec2::new(size.0 as f32, size.1 as f32);
+            let placements: Vec<_> = texts
+                .iter()
+                .filter(|(_, (text, _))| text.space == TextSpace::World)
+                .map(|(entity, (_, transform))| {
+                    TextPlacement::world(*entity, transform, camera.view_proj, viewport_size)
+                })
+                .collect();
+
+            self.text.prepare(
+                &self.device,
+                &self.queue,
+                fonts,
+                TextPassId::Camera(index),
+                size,
+                &placements,
+            );
+        }
 
-        // Update the number of indices to draw
-        self.num_indices = indices.len() as u32;
+        for window in windows {
+            let placements: Vec<_> = texts
+                .iter()
+                .filter(|(_, (text, _))| text.space == TextSpace::Screen(window))
+                .map(|(entity, (_, transform))| TextPlacement::screen(*entity, transform))
+                .collect();
+
+            if placements.is_empty() {
+                continue;
+            }
+
+            let Some(size) = self.target_size(&RenderTarget::Window(window)) else {
+                continue;
+            };
+
+            self.text.prepare(
+                &self.device,
+                &self.queue,
+                fonts,
+                TextPassId::Screen(window),
+                size,
+                &placements,
+            );
+        }
     }
 }
+
+fn create_sprite_pipeline(
+    device: &Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: TextureFormat,
+    depth_stencil: Option<wgpu::DepthStencilState>,
+) -> RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some("vs_main"),
+            buffers: &[Some(QuadVertex::desc()), Some(SpriteInstance::desc())],
+            compilation_options: PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some("fs_main"),
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
+            polygon_mode: wgpu::PolygonMode::Fill,
+            // Requires Features::DEPTH_CLIP_CONTROL
+            unclipped_depth: false,
+            // Requires Features::CONSERVATIVE_RASTERIZATION
+            conservative: false,
+        },
+        depth_stencil,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        cache: None,
+        multiview_mask: None,
+    })
+}
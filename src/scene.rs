@@ -0,0 +1,381 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ecs::{
+    entity::Entity,
+    reflect::{Reflect, ReflectData},
+    world::World,
+};
+
+/// Entities and resources captured from a [`World`] as JSON, keyed by type name
+///
+/// Only types registered with [`World::register_type`] are captured, spawning a scene also needs
+/// them to opt into `#[reflect(default)]`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DynamicScene {
+    #[serde(default)]
+    pub resources: BTreeMap<String, Value>,
+    #[serde(default)]
+    pub entities: Vec<DynamicEntity>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicEntity {
+    /// The entity in the world the scene was captured from, `Entity` fields in the scene refer to
+    /// these
+    pub entity: Entity,
+    pub components: BTreeMap<String, Value>,
+}
+
+impl DynamicScene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Captures the reflected components of the entities, other components are skipped
+    pub fn extract_entities(&mut self, world: &World, entities: impl IntoIterator<Item = Entity>) {
+        for entity in entities {
+            let components = world
+                .components_info
+                .iter()
+                .filter(|info| world.components.has_component(info.id(), entity))
+                .filter_map(|info| {
+                    let registration = world.type_registry().get(info.type_id()?)?;
+                    let component = registration.reflect()?.component(world, entity)?;
+                    Some((registration.name().to_string(), component.to_json()))
+                })
+                .collect();
+
+            self.entities.push(DynamicEntity { entity, components });
+        }
+    }
+
+    /// Captures a resource, fails if it doesn't exist or wasn't registered with
+    /// [`World::register_type`]
+    pub fn extract_resource<T: Reflect>(&mut self, world: &World) -> Result<(), anyhow::Error> {
+        let name = std::any::type_name::<T>();
+        let reflect = reflect_data(world, name)?;
+
+        let mut json = None;
+        if !reflect.resource_mut(world, |resource| json = Some(resource.to_json())) {
+            return Err(anyhow::anyhow!("No such resource {name}"));
+        }
+        self.resources.insert(name.to_string(), json.unwrap());
+
+        Ok(())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, anyhow::Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, anyhow::Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+
+        Self::from_json(&json)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {e}", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+        let path = path.as_ref();
+
+        std::fs::write(path, self.to_json()?)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {e}", path.display()))
+    }
+
+    /// Adds the entities of the scene to the world as new entities and inserts its resources
+    ///
+    /// `Entity` fields that refer to an entity of the scene are changed to the new entity. Returns
+    /// the new entity of every scene entity. Nothing is spawned if a component fails to load,
+    /// resources without `#[reflect(default)]` are updated in place and may already be changed
+    pub fn spawn(&self, world: &mut World) -> Result<HashMap<Entity, Entity>, anyhow::Error> {
+        let mut resources = Vec::new();
+        let mut existing_resources = Vec::new();
+        for (name, json) in &self.resources {
+            let reflect = reflect_data(world, name)?;
+
+            match reflect.default_value() {
+                Some(mut resource) => {
+                    resource
+                        .apply_json(json)
+                        .map_err(|e| anyhow::anyhow!("In resource {name}: {e}"))?;
+                    resources.push((reflect, resource));
+                }
+                None => existing_resources.push((reflect, name, json)),
+            }
+        }
+
+        let mut entities = Vec::new();
+        for scene_entity in &self.entities {
+            let mut components = Vec::new();
+            for (name, json) in &scene_entity.components {
+                let reflect = reflect_data(world, name)?;
+                let type_id = world.type_registry().get_by_name(name).unwrap().type_id();
+                if world.components_info.get_by_type_id(type_id).is_none() {
+                    return Err(anyhow::anyhow!("{name} is not a component"));
+                }
+                let mut component = reflect.default_value().ok_or_else(|| {
+                    anyhow::anyhow!("{name} can't be created, add #[reflect(default)] to it")
+                })?;
+                component.apply_json(json).map_err(|e| {
+                    anyhow::anyhow!("In component {name} of {:?}: {e}", scene_entity.entity)
+                })?;
+                components.push((reflect, component));
+            }
+            entities.push((scene_entity.entity, components));
+        }
+
+        for (reflect, name, json) in existing_resources {
+            let mut result = Ok(());
+            if !reflect.resource_mut(world, |resource| result = resource.apply_json(json)) {
+                return Err(anyhow::anyhow!(
+                    "{name} can't be created, add #[reflect(default)] to it"
+                ));
+            }
+            result.map_err(|e| anyhow::anyhow!("In resource {name}: {e}"))?;
+        }
+
+        let entity_map: HashMap<Entity, Entity> = entities
+            .iter()
+            .map(|(scene_entity, _)| (*scene_entity, world.add_entity(())))
+            .collect();
+
+        for (reflect, mut resource) in resources {
+            map_entities(&mut *resource, &entity_map);
+            reflect.insert_resource(world, resource);
+        }
+
+        for (scene_entity, components) in entities {
+            for (reflect, mut component) in components {
+                map_entities(&mut *component, &entity_map);
+                reflect.insert_component(world, entity_map[&scene_entity], component)?;
+            }
+        }
+
+        Ok(entity_map)
+    }
+}
+
+fn reflect_data(world: &World, name: &str) -> Result<ReflectData, anyhow::Error> {
+    world
+        .type_registry()
+        .get_by_name(name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown type {name}"))?
+        .reflect()
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("{name} isn't registered with register_type"))
+}
+
+/// Replaces the entities in the value that are keys of `entity_map`, including those in `Vec` and
+/// `Option` fields
+fn map_entities(value: &mut dyn Reflect, entity_map: &HashMap<Entity, Entity>) {
+    if let Some(entity) = value.downcast_mut::<Entity>() {
+        if let Some(mapped) = entity_map.get(entity) {
+            *entity = *mapped;
+        }
+        return;
+    }
+
+    for name in value.field_names() {
+        if let Some(field) = value.field_mut(name) {
+            map_entities(field, entity_map);
+        }
+    }
+    for item in value.items_mut() {
+        map_entities(item, entity_map);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use crate::{
+        State,
+        ecs::{
+            Component,
+            hierarchy::{Children, Parent},
+            rendering::Transform,
+            storage::sparse_set::SparseIndex,
+        },
+    };
+
+    use super::*;
+
+    #[derive(Reflect)]
+    #[reflect(default)]
+    struct Follow {
+        target: Entity,
+        offset: Vec2,
+    }
+
+    impl Default for Follow {
+        fn default() -> Self {
+            Self {
+                target: Entity::new_sparse_index(usize::MAX),
+                offset: Vec2::ZERO,
+            }
+        }
+    }
+
+    impl Component for Follow {}
+
+    #[derive(Reflect, Default)]
+    #[reflect(default)]
+    struct Score(u32);
+
+    #[derive(Reflect, Default)]
+    #[reflect(default)]
+    struct Squad {
+        members: Vec<Entity>,
+        leader: Option<Entity>,
+    }
+
+    impl Component for Squad {}
+
+    fn new_world() -> World {
+        let mut world = World::new();
+        world.register_component::<Transform>();
+        world.register_component::<Follow>();
+        world.register_component::<Squad>();
+        world.register_type::<Transform>();
+        world.register_type::<Follow>();
+        world.register_type::<Squad>();
+        world.register_type::<Score>();
+        world
+    }
+
+    #[test]
+    fn test_scene_round_trip() {
+        let mut world = new_world();
+        world.insert_resource(Score(3));
+        let player = world.add_entity(Transform {
+            position: glam::Vec3::new(1., 2., 3.),
+            ..Default::default()
+        });
+        let camera = world.add_entity(Follow {
+            target: player,
+            offset: Vec2::new(0., 5.),
+        });
+
+        let mut scene = DynamicScene::new();
+        scene.extract_entities(&world, [player, camera]);
+        scene.extract_resource::<Score>(&world).unwrap();
+        let scene = DynamicScene::from_json(&scene.to_json().unwrap()).unwrap();
+
+        let mut world = new_world();
+        // Shifts the new entities so they differ from the captured ones
+        world.add_entity(());
+        let entity_map = scene.spawn(&mut world).unwrap();
+
+        let new_player = entity_map[&player];
+        assert_ne!(new_player, player);
+        assert_eq!(
+            world
+                .get_component::<Transform>(new_player)
+                .unwrap()
+                .position,
+            glam::Vec3::new(1., 2., 3.)
+        );
+        let follow = world.get_component::<Follow>(entity_map[&camera]).unwrap();
+        assert_eq!(follow.target, new_player);
+        assert_eq!(follow.offset, Vec2::new(0., 5.));
+        assert_eq!(world.read_resource::<Score>().unwrap().0, 3);
+    }
+
+    #[test]
+    fn test_scene_hierarchy() {
+        // Uses the worlds of apps, which register the hierarchy components for reflection
+        let mut world = State::new().world;
+        let parent = world.add_entity(Transform::default());
+        let child = world.add_entity(Transform::default());
+        world.set_parent(child, parent);
+
+        let mut scene = DynamicScene::new();
+        scene.extract_entities(&world, [parent, child]);
+        let scene = DynamicScene::from_json(&scene.to_json().unwrap()).unwrap();
+
+        let mut world = State::new().world;
+        world.add_entity(());
+        let entity_map = scene.spawn(&mut world).unwrap();
+
+        let (new_parent, new_child) = (entity_map[&parent], entity_map[&child]);
+        assert_ne!(new_parent, parent);
+        assert_eq!(
+            world.get_component::<Parent>(new_child).unwrap().get(),
+            new_parent
+        );
+        assert_eq!(
+            &**world.get_component::<Children>(new_parent).unwrap(),
+            &[new_child]
+        );
+    }
+
+    #[test]
+    fn test_scene_entity_lists() {
+        let mut world = new_world();
+        let a = world.add_entity(());
+        let b = world.add_entity(());
+        let squad = world.add_entity(Squad {
+            members: vec![a, b],
+            leader: Some(b),
+        });
+
+        let mut scene = DynamicScene::new();
+        scene.extract_entities(&world, [a, b, squad]);
+        let scene = DynamicScene::from_json(&scene.to_json().unwrap()).unwrap();
+
+        let mut world = new_world();
+        world.add_entity(());
+        let entity_map = scene.spawn(&mut world).unwrap();
+
+        let squad = world.get_component::<Squad>(entity_map[&squad]).unwrap();
+        assert_eq!(squad.members, [entity_map[&a], entity_map[&b]]);
+        assert_eq!(squad.leader, Some(entity_map[&b]));
+        assert_ne!(squad.leader, Some(b));
+    }
+
+    #[test]
+    fn test_scene_errors() {
+        let mut world = new_world();
+        let entity_count = world.entities().len();
+
+        let unknown = r#"{ "entities": [{
+            "entity": { "index": 0, "generation": 0 },
+            "components": { "game::Unknown": {} }
+        }] }"#;
+        let error = DynamicScene::from_json(unknown)
+            .unwrap()
+            .spawn(&mut world)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Unknown type game::Unknown");
+
+        let mismatched = format!(
+            r#"{{ "entities": [{{
+                "entity": {{ "index": 0, "generation": 0 }},
+                "components": {{ "{}": {{ "position": {{ "x": "left" }} }} }}
+            }}] }}"#,
+            std::any::type_name::<Transform>()
+        );
+        let error = DynamicScene::from_json(&mismatched)
+            .unwrap()
+            .spawn(&mut world)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("In field \"x\""), "{error}");
+        assert!(error.contains("Expected f32, found \"left\""), "{error}");
+
+        assert_eq!(world.entities().len(), entity_count);
+    }
+}
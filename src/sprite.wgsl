@@ -0,0 +1,54 @@
+struct VertexInput {
+    @location(0) corner: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+};
+
+struct InstanceInput {
+    @location(2) position: vec3<f32>,
+    @location(3) axis_x: vec2<f32>,
+    @location(4) axis_y: vec2<f32>,
+    @location(5) anchor: vec2<f32>,
+    @location(6) uv_min: vec2<f32>,
+    @location(7) uv_max: vec2<f32>,
+    @location(8) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0)
+var sprite_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var sprite_sampler: sampler;
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    let offset = model.corner - instance.anchor;
+    let position = instance.position
+        + vec3<f32>(instance.axis_x * offset.x + instance.axis_y * offset.y, 0.0);
+
+    var out: VertexOutput;
+    out.uv = mix(instance.uv_min, instance.uv_max, model.uv);
+    out.color = instance.color;
+    out.clip_position = camera.view_proj * vec4<f32>(position, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(model: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(sprite_texture, sprite_sampler, model.uv) * model.color;
+    // The pipeline blends with premultiplied alpha
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
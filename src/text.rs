@@ -0,0 +1,498 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use glam::{Mat4, Vec2, Vec3, Vec4};
+use glyphon::{
+    Attrs, Buffer, Cache, Color, Family, FontSystem, Metrics, Resolution, Shaping, SwashCache,
+    TextArea, TextAtlas, TextBounds, TextRenderer, Viewport, fontdb,
+};
+
+use crate::{
+    ecs::{Component, entity::Entity, rendering::Transform},
+    window::WindowRef,
+};
+
+/// Refers to a font loaded in the [`Fonts`] resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FontHandle(u32);
+
+/// Fonts available to [`Text2d`], the system fonts are loaded when the window is created
+pub struct Fonts {
+    pub(crate) font_system: FontSystem,
+    families: HashMap<FontHandle, String>,
+    next_id: u32,
+}
+
+impl Fonts {
+    pub(crate) fn new() -> Self {
+        Self {
+            font_system: FontSystem::new_with_locale_and_db(
+                "en-US".to_string(),
+                fontdb::Database::new(),
+            ),
+            families: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    pub(crate) fn load_system_fonts(&mut self) {
+        self.font_system.db_mut().load_system_fonts();
+    }
+
+    /// Loads a TrueType or OpenType font from memory
+    pub fn load_bytes(&mut self, bytes: Vec<u8>) -> Result<FontHandle, anyhow::Error> {
+        let db = self.font_system.db_mut();
+        let ids = db.load_font_source(fontdb::Source::Binary(Arc::new(bytes)));
+        let family = ids
+            .first()
+            .and_then(|id| db.face(*id))
+            .and_then(|face| face.families.first())
+            .map(|(family, _)| family.clone())
+            .ok_or_else(|| anyhow::anyhow!("No font face found"))?;
+
+        let handle = FontHandle(self.next_id);
+        self.next_id += 1;
+        self.families.insert(handle, family);
+
+        Ok(handle)
+    }
+
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<FontHandle, anyhow::Error> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+
+        self.load_bytes(bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to load {}: {e}", path.display()))
+    }
+
+    /// The family name of a loaded font
+    pub fn family_name(&self, font: FontHandle) -> Option<&str> {
+        self.families.get(&font).map(String::as_str)
+    }
+
+    fn family(&self, font: Option<FontHandle>) -> Family<'_> {
+        font.and_then(|font| self.family_name(font))
+            .map(Family::Name)
+            .unwrap_or(Family::SansSerif)
+    }
+}
+
+/// A run of text with its own style
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextSection {
+    pub text: String,
+    /// The default sans-serif font if `None`
+    pub font: Option<FontHandle>,
+    /// In pixels for screen space text, in world units otherwise
+    pub size: f32,
+    /// RGBA like [`Sprite::color`](crate::ecs::rendering::Sprite::color)
+    pub color: Vec4,
+}
+
+impl TextSection {
+    pub fn new(text: impl Into<String>, size: f32) -> Self {
+        Self {
+            text: text.into(),
+            font: None,
+            size,
+            color: Vec4::ONE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextSpace {
+    /// Drawn by every camera over its sprites
+    #[default]
+    World,
+    /// Drawn over everything in a window, the [`Transform`] `x` and `y` are in pixels from the
+    /// top-left corner of the window
+    Screen(WindowRef),
+}
+
+/// Text drawn with its top-left corner at the [`Transform`] position
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Text2d {
+    pub sections: Vec<TextSection>,
+    /// Alignment of the lines within the text box
+    pub align: TextAlign,
+    /// Size of the text box, lines wrap at its width and are clipped at its height. The text
+    /// box fits the text if `None`
+    pub bounds: Option<Vec2>,
+    pub space: TextSpace,
+}
+
+impl Text2d {
+    pub fn new(text: impl Into<String>, size: f32) -> Self {
+        Self {
+            sections: vec![TextSection::new(text, size)],
+            ..Default::default()
+        }
+    }
+
+    /// Text drawn over everything in the primary window
+    pub fn screen(text: impl Into<String>, size: f32) -> Self {
+        Self {
+            space: TextSpace::Screen(WindowRef::Primary),
+            ..Self::new(text, size)
+        }
+    }
+}
+
+impl Component for Text2d {}
+
+/// Where a text is drawn in pixels and how much it's scaled
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TextPlacement {
+    pub(crate) entity: Entity,
+    pub(crate) position: Vec2,
+    pub(crate) scale: f32,
+}
+
+impl TextPlacement {
+    /// Projects a world space text through a camera into a viewport of `viewport_size` pixels
+    pub(crate) fn world(
+        entity: Entity,
+        transform: &Transform,
+        view_proj: Mat4,
+        viewport_size: Vec2,
+    ) -> Self {
+        let ndc = view_proj.project_point3(transform.position);
+        let position = Vec2::new(
+            (ndc.x + 1.) / 2. * viewport_size.x,
+            (1. - ndc.y) / 2. * viewport_size.y,
+        );
+
+        // How many pixels a world unit along x covers
+        let axis = view_proj.transform_vector3(Vec3::X).truncate() * viewport_size / 2.;
+
+        Self {
+            entity,
+            position,
+            scale: axis.length() * transform.scale.x,
+        }
+    }
+
+    pub(crate) fn screen(entity: Entity, transform: &Transform) -> Self {
+        Self {
+            entity,
+            position: transform.position.truncate(),
+            scale: transform.scale.x,
+        }
+    }
+}
+
+/// Converts a color to the sRGB bytes glyphon expects, sprite colors are linear
+fn glyphon_color(color: Vec4) -> Color {
+    let srgb = |c: f32| {
+        let c = c.clamp(0., 1.);
+        let c = if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1. / 2.4) - 0.055
+        };
+        (c * 255.).round() as u8
+    };
+
+    Color::rgba(
+        srgb(color.x),
+        srgb(color.y),
+        srgb(color.z),
+        (color.w.clamp(0., 1.) * 255.).round() as u8,
+    )
+}
+
+fn line_height(size: f32) -> f32 {
+    size * 1.2
+}
+
+/// A text shaped by glyphon, reshaped whenever the [`Text2d`] changes
+struct ShapedText {
+    text: Text2d,
+    buffer: Buffer,
+}
+
+impl ShapedText {
+    fn new(fonts: &mut Fonts, text: &Text2d) -> Self {
+        let mut shaped = Self {
+            text: text.clone(),
+            buffer: Buffer::new_empty(Metrics::new(1., 1.)),
+        };
+        shaped.shape(fonts);
+
+        shaped
+    }
+
+    fn shape(&mut self, fonts: &mut Fonts) {
+        let text = &self.text;
+        let size = text.sections.first().map_or(16., |section| section.size);
+        self.buffer
+            .set_metrics(Metrics::new(size.max(0.01), line_height(size).max(0.01)));
+        self.buffer.set_size(
+            text.bounds.map(|bounds| bounds.x),
+            text.bounds.map(|bounds| bounds.y),
+        );
+
+        let align = match text.align {
+            TextAlign::Left => glyphon::cosmic_text::Align::Left,
+            TextAlign::Center => glyphon::cosmic_text::Align::Center,
+            TextAlign::Right => glyphon::cosmic_text::Align::Right,
+        };
+
+        let spans = text.sections.iter().map(|section| {
+            let attrs = Attrs::new()
+                .family(fonts.family(section.font))
+                .color(glyphon_color(section.color))
+                .metrics(Metrics::new(
+                    section.size.max(0.01),
+                    line_height(section.size).max(0.01),
+                ));
+            (section.text.as_str(), attrs)
+        });
+        self.buffer
+            .set_rich_text(spans, &Attrs::new(), Shaping::Advanced, Some(align));
+        self.buffer
+            .shape_until_scroll(&mut fonts.font_system, false);
+    }
+}
+
+/// A glyphon renderer with the viewport it was prepared for
+pub(crate) struct TextPass {
+    viewport: Viewport,
+    renderer: TextRenderer,
+}
+
+impl TextPass {
+    pub(crate) fn render(&self, atlas: &TextAtlas, pass: &mut wgpu::RenderPass<'_>) {
+        if let Err(e) = self.renderer.render(atlas, &self.viewport, pass) {
+            tracing::error!("Failed to render text: {e}");
+        }
+    }
+}
+
+/// Which pass a text is drawn in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TextPassId {
+    /// Over the sprites of the camera with this index
+    Camera(usize),
+    Screen(WindowRef),
+}
+
+/// The GPU side of text rendering, owned by the [`Renderer`](crate::renderer::Renderer)
+pub(crate) struct GpuText {
+    cache: Cache,
+    pub(crate) atlas: TextAtlas,
+    swash_cache: SwashCache,
+    /// Camera passes have to match the depth attachment when the depth buffer is enabled
+    depth_stencil: Option<wgpu::DepthStencilState>,
+    camera_passes: Vec<TextPass>,
+    screen_passes: HashMap<WindowRef, TextPass>,
+    /// The windows with screen space text this frame
+    screen_windows: Vec<WindowRef>,
+    shaped: HashMap<Entity, ShapedText>,
+}
+
+impl GpuText {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        depth_stencil: Option<wgpu::DepthStencilState>,
+    ) -> Self {
+        let cache = Cache::new(device);
+        let atlas = TextAtlas::new(device, queue, &cache, format);
+
+        Self {
+            cache,
+            atlas,
+            swash_cache: SwashCache::new(),
+            depth_stencil,
+            camera_passes: Vec::new(),
+            screen_passes: HashMap::new(),
+            screen_windows: Vec::new(),
+            shaped: HashMap::new(),
+        }
+    }
+
+    /// Shapes new and changed texts and drops the ones of entities that don't have a text anymore
+    pub(crate) fn update_texts<'a>(
+        &mut self,
+        fonts: &mut Fonts,
+        texts: impl IntoIterator<Item = (Entity, &'a Text2d)>,
+    ) {
+        let mut seen = Vec::new();
+
+        for (entity, text) in texts {
+            seen.push(entity);
+
+            match self.shaped.get_mut(&entity) {
+                Some(shaped) if shaped.text == *text => {}
+                Some(shaped) => {
+                    shaped.text = text.clone();
+                    shaped.shape(fonts);
+                }
+                None => {
+                    self.shaped.insert(entity, ShapedText::new(fonts, text));
+                }
+            }
+        }
+
+        self.shaped.retain(|entity, _| seen.contains(entity));
+    }
+
+    /// Starts a new frame, glyphs that aren't prepared again can be evicted from the atlas
+    pub(crate) fn begin_frame(&mut self, windows: &[WindowRef]) {
+        self.atlas.trim();
+        self.screen_windows.clear();
+        self.screen_passes
+            .retain(|window, _| windows.contains(window));
+    }
+
+    pub(crate) fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        fonts: &mut Fonts,
+        pass: TextPassId,
+        resolution: (u32, u32),
+        placements: &[TextPlacement],
+    ) {
+        let new_pass = |atlas: &mut TextAtlas, depth_stencil| TextPass {
+            viewport: Viewport::new(device, &self.cache),
+            renderer: TextRenderer::new(
+                atlas,
+                device,
+                wgpu::MultisampleState::default(),
+                depth_stencil,
+            ),
+        };
+
+        let pass = match pass {
+            TextPassId::Camera(index) => {
+                while self.camera_passes.len() <= index {
+                    let pass = new_pass(&mut self.atlas, self.depth_stencil.clone());
+                    self.camera_passes.push(pass);
+                }
+                &mut self.camera_passes[index]
+            }
+            TextPassId::Screen(window) => {
+                self.screen_windows.push(window);
+                self.screen_passes
+                    .entry(window)
+                    .or_insert_with(|| new_pass(&mut self.atlas, None))
+            }
+        };
+
+        pass.viewport.update(
+            queue,
+            Resolution {
+                width: resolution.0,
+                height: resolution.1,
+            },
+        );
+
+        let areas = placements.iter().filter_map(|placement| {
+            let shaped = self.shaped.get(&placement.entity)?;
+            let bounds = shaped.text.bounds.map_or(TextBounds::default(), |bounds| {
+                let max = placement.position + bounds * placement.scale;
+                TextBounds {
+                    left: placement.position.x.floor() as i32,
+                    top: placement.position.y.floor() as i32,
+                    right: max.x.ceil() as i32,
+                    bottom: max.y.ceil() as i32,
+                }
+            });
+
+            Some(TextArea {
+                buffer: &shaped.buffer,
+                left: placement.position.x,
+                top: placement.position.y,
+                scale: placement.scale,
+                bounds,
+                default_color: Color::rgb(255, 255, 255),
+                custom_glyphs: &[],
+            })
+        });
+
+        if let Err(e) = pass.renderer.prepare(
+            device,
+            queue,
+            &mut fonts.font_system,
+            &mut self.atlas,
+            &pass.viewport,
+            areas,
+            &mut self.swash_cache,
+        ) {
+            tracing::error!("Failed to prepare text: {e}");
+        }
+    }
+
+    pub(crate) fn camera_pass(&self, index: usize) -> Option<&TextPass> {
+        self.camera_passes.get(index)
+    }
+
+    /// The screen space text of a window, `None` if it has none this frame
+    pub(crate) fn screen_pass(&self, window: WindowRef) -> Option<&TextPass> {
+        if self.screen_windows.contains(&window) {
+            self.screen_passes.get(&window)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{camera::orthographic_camera::OrthographicCamera, ecs::world::World};
+
+    use super::*;
+
+    #[test]
+    fn test_world_text_placement() {
+        let camera = OrthographicCamera::new(-400., 400., -300., 300.);
+        let mut world = World::new();
+        world.register_component::<Transform>();
+        let entity = world.add_entity(Transform::default());
+        let viewport_size = Vec2::new(800., 600.);
+        let text = Transform {
+            position: Vec3::new(-400., 300., 0.),
+            ..Default::default()
+        };
+
+        let view_proj = camera.build_view_projection_matrix(&Transform::default());
+        let placement = TextPlacement::world(entity, &text, view_proj, viewport_size);
+        assert!(placement.position.abs_diff_eq(Vec2::ZERO, 0.001));
+        assert!((placement.scale - 1.).abs() < 0.001);
+
+        // Zooming the camera in scales the text up around the center
+        let zoomed = Transform {
+            scale: Vec2::splat(2.),
+            ..Default::default()
+        };
+        let view_proj = camera.build_view_projection_matrix(&zoomed);
+        let placement = TextPlacement::world(entity, &text, view_proj, viewport_size);
+        assert!(
+            placement
+                .position
+                .abs_diff_eq(Vec2::new(-400., -300.), 0.001)
+        );
+        assert!((placement.scale - 2.).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_glyphon_color() {
+        assert_eq!(glyphon_color(Vec4::ONE), Color::rgba(255, 255, 255, 255));
+        assert_eq!(
+            glyphon_color(Vec4::new(0., 0.5, 2., 0.5)),
+            Color::rgba(0, 188, 255, 128)
+        );
+    }
+}
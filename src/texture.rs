@@ -0,0 +1,205 @@
+use std::{collections::HashMap, path::Path};
+
+use glam::Vec2;
+
+/// Refers to an image in the [`Images`] resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TextureHandle(u32);
+
+/// Decoded RGBA8 pixels in sRGB
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, data: Vec<u8>) -> Self {
+        assert_eq!(
+            data.len(),
+            (width * height * 4) as usize,
+            "Image data must be RGBA8"
+        );
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    /// Decodes an image from memory, the format is guessed from the content
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, anyhow::Error> {
+        let image = image::load_from_memory(bytes)?.into_rgba8();
+
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            data: image.into_raw(),
+        })
+    }
+
+    /// Encodes the image as a PNG file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), anyhow::Error> {
+        let path = path.as_ref();
+
+        image::save_buffer(
+            path,
+            &self.data,
+            self.width,
+            self.height,
+            image::ExtendedColorType::Rgba8,
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to save {}: {e}", path.display()))
+    }
+
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width as f32, self.height as f32)
+    }
+}
+
+/// Images used by sprites, they are uploaded to the GPU before rendering
+pub struct Images {
+    images: HashMap<TextureHandle, Image>,
+    next_id: u32,
+}
+
+impl Images {
+    pub(crate) fn new() -> Self {
+        Self {
+            images: HashMap::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn add(&mut self, image: Image) -> TextureHandle {
+        let handle = TextureHandle(self.next_id);
+        self.next_id += 1;
+        self.images.insert(handle, image);
+
+        handle
+    }
+
+    /// Loads and decodes an image file (only PNG is supported)
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<TextureHandle, anyhow::Error> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {e}", path.display()))?;
+        let image = Image::from_bytes(&bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to decode {}: {e}", path.display()))?;
+
+        Ok(self.add(image))
+    }
+
+    pub fn get(&self, handle: TextureHandle) -> Option<&Image> {
+        self.images.get(&handle)
+    }
+
+    /// Removes the image, sprites still using the handle are drawn without a texture
+    pub fn remove(&mut self, handle: TextureHandle) -> Option<Image> {
+        self.images.remove(&handle)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (TextureHandle, &Image)> {
+        self.images.iter().map(|(handle, image)| (*handle, image))
+    }
+}
+
+/// An image uploaded to the GPU with the bind group used to sample it
+pub(crate) struct GpuTexture {
+    pub(crate) bind_group: wgpu::BindGroup,
+}
+
+impl GpuTexture {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        image: &Image,
+    ) -> Self {
+        let size = wgpu::Extent3d {
+            width: image.width.max(1),
+            height: image.height.max(1),
+            depth_or_array_layers: 1,
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Sprite Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        if image.width > 0 && image.height > 0 {
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &image.data,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * image.width),
+                    rows_per_image: Some(image.height),
+                },
+                size,
+            );
+        }
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("texture_bind_group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        });
+
+        Self { bind_group }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_png() {
+        let mut bytes = Vec::new();
+        let pixels = [255, 0, 0, 255, 0, 255, 0, 128];
+        image::write_buffer_with_format(
+            &mut std::io::Cursor::new(&mut bytes),
+            &pixels,
+            2,
+            1,
+            image::ExtendedColorType::Rgba8,
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+
+        let image = Image::from_bytes(&bytes).unwrap();
+        assert_eq!(image, Image::new(2, 1, pixels.to_vec()));
+
+        let mut images = Images::new();
+        let handle = images.add(image);
+        assert_eq!(images.get(handle).unwrap().size(), Vec2::new(2., 1.));
+        assert!(images.remove(handle).is_some());
+        assert!(images.get(handle).is_none());
+    }
+}
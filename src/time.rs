@@ -2,18 +2,44 @@ use std::time::Instant;
 
 pub struct Time {
     pub(crate) last_frame_time: Instant,
+    delta: f32,
+    frame_count: u64,
 }
 
 impl Time {
     pub(crate) fn new() -> Self {
         Self {
             last_frame_time: Instant::now(),
+            delta: 0.,
+            frame_count: 0,
         }
     }
 
+    /// Time in seconds between the start of the previous frame and the start of this one
     pub fn delta_time(&self) -> f32 {
-        Instant::now()
-            .duration_since(self.last_frame_time)
-            .as_secs_f32()
+        self.delta
+    }
+
+    /// How many frames have started since the app was launched
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Starts a new frame, measuring the delta from the wall clock
+    pub(crate) fn update(&mut self) {
+        let now = Instant::now();
+        self.advance(now.duration_since(self.last_frame_time).as_secs_f32());
+        self.last_frame_time = now;
+    }
+
+    /// Restarts the wall clock from now, so the next delta doesn't count the time spent suspended
+    pub(crate) fn reset_frame(&mut self) {
+        self.last_frame_time = Instant::now();
+    }
+
+    /// Starts a new frame with the given delta, used when replaying recorded input
+    pub(crate) fn advance(&mut self, delta: f32) {
+        self.delta = delta;
+        self.frame_count += 1;
     }
 }
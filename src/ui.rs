@@ -0,0 +1,156 @@
+use winit::event::{ElementState, WindowEvent};
+
+use crate::{
+    App, Plugin,
+    ecs::{
+        scheduler::{Access, Res, ResMut, SystemParam},
+        world::{UnsafeWorldCell, World},
+    },
+    renderer::Renderer,
+};
+
+/// Adds egui to the primary window, systems draw UI with the [`EguiContext`] param
+///
+/// The UI is drawn over everything else and game input is suppressed while egui uses the
+/// pointer or keyboard
+pub struct EguiPlugin;
+
+impl Plugin for EguiPlugin {
+    fn build(&self, mut app: App) -> App {
+        app.insert_resource(Egui::new());
+        app
+    }
+}
+
+/// The egui context and whether a pass is running, one pass runs every frame
+pub struct Egui {
+    pub(crate) context: egui::Context,
+    in_pass: bool,
+}
+
+impl Egui {
+    fn new() -> Self {
+        Self {
+            context: egui::Context::default(),
+            in_pass: false,
+        }
+    }
+
+    pub(crate) fn begin_pass(&mut self, input: egui::RawInput) {
+        self.context.begin_pass(input);
+        self.in_pass = true;
+    }
+
+    /// Ends the pass if one is running
+    pub(crate) fn end_pass(&mut self) -> Option<egui::FullOutput> {
+        std::mem::take(&mut self.in_pass).then(|| self.context.end_pass())
+    }
+}
+
+/// Draws UI with egui, requires the [`EguiPlugin`]
+pub struct EguiContext<'w> {
+    egui: Res<'w, Egui>,
+}
+
+impl EguiContext<'_> {
+    pub fn ctx(&self) -> &egui::Context {
+        &self.egui.context
+    }
+}
+
+impl SystemParam for EguiContext<'_> {
+    type State = <Res<'static, Egui> as SystemParam>::State;
+
+    type Item<'world, 'state> = EguiContext<'world>;
+
+    fn init_state(world: &mut World) -> Self::State {
+        <Res<'static, Egui> as SystemParam>::init_state(world)
+    }
+
+    fn init_access(world: &mut World, access: &mut Access) {
+        <Res<'static, Egui> as SystemParam>::init_access(world, access)
+    }
+
+    unsafe fn get_param<'w, 's>(
+        world: UnsafeWorldCell<'w>,
+        state: &'s mut Self::State,
+    ) -> Self::Item<'w, 's> {
+        let egui = unsafe { <Res<'static, Egui> as SystemParam>::get_param(world, state) };
+
+        EguiContext { egui }
+    }
+}
+
+/// Ends the egui pass of the frame and tessellates it to be drawn at the end of the frame
+pub(crate) fn end_egui_pass(mut egui: ResMut<Egui>, mut renderer: ResMut<Renderer>) {
+    if let Some(output) = egui.end_pass() {
+        renderer.prepare_egui(output);
+    }
+}
+
+/// Whether a window event still reaches the game after egui handled it
+///
+/// Releases always do, otherwise a key held when egui took focus would stay pressed
+pub(crate) fn forward_to_game(event: &WindowEvent, consumed: bool) -> bool {
+    if !consumed {
+        return true;
+    }
+
+    match event {
+        WindowEvent::KeyboardInput { event, .. } => event.state == ElementState::Released,
+        WindowEvent::MouseInput { state, .. } => *state == ElementState::Released,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::{
+        dpi::PhysicalPosition,
+        event::{DeviceId, MouseButton},
+    };
+
+    use crate::input::replay::{InputRecording, RecordedFrame};
+
+    use super::*;
+
+    #[test]
+    fn test_forward_to_game() {
+        let click = |state| WindowEvent::MouseInput {
+            device_id: DeviceId::dummy(),
+            state,
+            button: MouseButton::Left,
+        };
+        let cursor_moved = WindowEvent::CursorMoved {
+            device_id: DeviceId::dummy(),
+            position: PhysicalPosition::new(1., 2.),
+        };
+
+        assert!(forward_to_game(&click(ElementState::Pressed), false));
+        assert!(!forward_to_game(&click(ElementState::Pressed), true));
+        assert!(forward_to_game(&click(ElementState::Released), true));
+        assert!(!forward_to_game(&cursor_moved, true));
+        assert!(forward_to_game(&WindowEvent::Focused(true), false));
+    }
+
+    fn show_window(egui: EguiContext) {
+        egui::Window::new("Test").show(egui.ctx(), |ui| ui.label("Hello"));
+    }
+
+    #[test]
+    fn test_egui_without_renderer() {
+        let mut recording = InputRecording::new();
+        for frame in 1..=2 {
+            recording.push_frame(RecordedFrame {
+                frame,
+                delta: 0.1,
+                events: vec![],
+            });
+        }
+
+        let mut app = App::new().add_plugin(EguiPlugin).add_system(show_window);
+        app.replay(&recording);
+
+        assert!(!app.world().read_resource::<Egui>().unwrap().in_pass);
+    }
+}
@@ -1,27 +1,90 @@
+/// A corner of the unit quad shared by every sprite instance
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
-pub struct VertexColored {
-    pub position: [f32; 3],
-    pub color: [f32; 3],
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct QuadVertex {
+    /// Relative to the sprite size, `(0.5, 0.5)` is the top-right corner
+    pub corner: [f32; 2],
+    /// How far along the UV rect of the instance this corner is
+    pub uv: [f32; 2],
 }
 
-impl VertexColored {
+impl QuadVertex {
+    pub const QUAD: [QuadVertex; 4] = [
+        QuadVertex {
+            corner: [-0.5, 0.5],
+            uv: [0., 0.],
+        },
+        QuadVertex {
+            corner: [-0.5, -0.5],
+            uv: [0., 1.],
+        },
+        QuadVertex {
+            corner: [0.5, -0.5],
+            uv: [1., 1.],
+        },
+        QuadVertex {
+            corner: [0.5, 0.5],
+            uv: [1., 0.],
+        },
+    ];
+
+    pub const INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
     pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 2] =
+            wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2];
+
         wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<VertexColored>() as wgpu::BufferAddress,
+            array_stride: std::mem::size_of::<QuadVertex>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-                wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-            ],
+            attributes: &ATTRIBUTES,
         }
     }
 }
+
+/// Everything needed to place, color and texture one sprite, extracted on the CPU every frame
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpriteInstance {
+    /// Where the anchor of the sprite is
+    pub position: [f32; 3],
+    /// The rotated and scaled width of the sprite
+    pub axis_x: [f32; 2],
+    /// The rotated and scaled height of the sprite
+    pub axis_y: [f32; 2],
+    pub anchor: [f32; 2],
+    pub uv_min: [f32; 2],
+    pub uv_max: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl SpriteInstance {
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        const ATTRIBUTES: [wgpu::VertexAttribute; 7] = wgpu::vertex_attr_array![
+            2 => Float32x3,
+            3 => Float32x2,
+            4 => Float32x2,
+            5 => Float32x2,
+            6 => Float32x2,
+            7 => Float32x2,
+            8 => Float32x4,
+        ];
+
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SpriteInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &ATTRIBUTES,
+        }
+    }
+
+    /// Position of a quad corner, the same computation the vertex shader does
+    #[cfg(test)]
+    pub fn corner_position(&self, corner: glam::Vec2) -> glam::Vec3 {
+        use glam::{Vec2, Vec3};
+
+        let offset = corner - Vec2::from(self.anchor);
+        let offset = Vec2::from(self.axis_x) * offset.x + Vec2::from(self.axis_y) * offset.y;
+
+        Vec3::from(self.position) + offset.extend(0.)
+    }
+}
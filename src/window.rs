@@ -1,14 +1,346 @@
-#[derive(Debug)]
+use glam::IVec2;
+pub use wgpu::PresentMode;
+pub use winit::window::CursorGrabMode;
+use winit::{
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
+    monitor::MonitorHandle,
+    window::{Fullscreen, Window as WinitWindow, WindowAttributes},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::{Component, entity::Entity, events::Event};
+
+/// Identifies a window, either the primary window created from the [`WindowDescriptor`] resource,
+/// or an entity with a [`Window`] component
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum WindowRef {
+    #[default]
+    Primary,
+    Entity(Entity),
+}
+
+/// How the window is displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowMode {
+    #[default]
+    Windowed,
+    /// Covers the whole monitor without changing its video mode
+    BorderlessFullscreen,
+    /// Takes exclusive control of the monitor using its highest resolution video mode
+    Fullscreen,
+}
+
+impl WindowMode {
+    fn to_fullscreen(self, monitor: Option<MonitorHandle>) -> Option<Fullscreen> {
+        match self {
+            WindowMode::Windowed => None,
+            WindowMode::BorderlessFullscreen => Some(Fullscreen::Borderless(monitor)),
+            WindowMode::Fullscreen => {
+                let video_mode = monitor?.video_modes().max_by_key(|video_mode| {
+                    (
+                        video_mode.size().width * video_mode.size().height,
+                        video_mode.refresh_rate_millihertz(),
+                    )
+                })?;
+
+                Some(Fullscreen::Exclusive(video_mode))
+            }
+        }
+    }
+}
+
+/// Describes the window created when the app starts
+///
+/// Insert it as a resource before running the app to override the defaults
+#[derive(Debug, Clone)]
+pub struct WindowDescriptor {
+    pub title: String,
+    /// Width in logical pixels
+    pub width: f32,
+    /// Height in logical pixels
+    pub height: f32,
+    /// Position of the top-left corner of the window in physical pixels, the platform decides
+    /// if `None`
+    pub position: Option<IVec2>,
+    pub resizable: bool,
+    pub decorations: bool,
+    pub mode: WindowMode,
+    /// Controls vsync, `AutoVsync` and `AutoNoVsync` are supported everywhere, other modes
+    /// depend on the platform
+    pub present_mode: PresentMode,
+    /// Exits the app when the window is asked to close, disable it to handle
+    /// [`CloseRequested`] yourself (e.g. to show a confirmation dialog) and send
+    /// [`AppExit`](crate::AppExit) when you're done
+    pub exit_on_close: bool,
+}
+
+impl Default for WindowDescriptor {
+    fn default() -> Self {
+        Self {
+            title: "Dahhan".to_string(),
+            width: 128.,
+            height: 128.,
+            position: None,
+            resizable: true,
+            decorations: true,
+            mode: WindowMode::Windowed,
+            present_mode: PresentMode::AutoVsync,
+            exit_on_close: true,
+        }
+    }
+}
+
+impl WindowDescriptor {
+    pub(crate) fn attributes(&self, monitor: Option<MonitorHandle>) -> WindowAttributes {
+        let mut attributes = WinitWindow::default_attributes()
+            .with_title(&self.title)
+            .with_inner_size(LogicalSize::new(self.width, self.height))
+            .with_resizable(self.resizable)
+            .with_decorations(self.decorations)
+            .with_fullscreen(self.mode.to_fullscreen(monitor));
+
+        if let Some(position) = self.position {
+            attributes = attributes.with_position(PhysicalPosition::new(position.x, position.y));
+        }
+
+        attributes
+    }
+}
+
+/// The state of a window, changes made by systems are applied to the window at the end of the
+/// frame
+///
+/// The primary window is available as a resource, additional windows are opened by adding
+/// entities with this component, and closed by removing it. When creating a window from the
+/// component a `width`/`height` of 0 lets the platform pick the size
+#[derive(Debug, Clone, PartialEq)]
 pub struct Window {
+    /// Width in physical pixels
     pub width: f32,
+    /// Height in physical pixels
     pub height: f32,
+    pub title: String,
+    pub mode: WindowMode,
+    /// Position of the top-left corner of the window in physical pixels, if the platform
+    /// supports it
+    pub position: Option<IVec2>,
+    pub resizable: bool,
+    pub decorations: bool,
+    pub cursor_visible: bool,
+    pub cursor_grab: CursorGrabMode,
+    pub(crate) scale_factor: f64,
+    pub(crate) focused: bool,
 }
 
 impl Window {
     pub fn new() -> Self {
+        Self::from_descriptor(&WindowDescriptor::default())
+    }
+
+    pub(crate) fn from_descriptor(descriptor: &WindowDescriptor) -> Self {
         Self {
             width: 0.,
             height: 0.,
+            title: descriptor.title.clone(),
+            mode: descriptor.mode,
+            position: descriptor.position,
+            resizable: descriptor.resizable,
+            decorations: descriptor.decorations,
+            cursor_visible: true,
+            cursor_grab: CursorGrabMode::None,
+            scale_factor: 1.,
+            focused: false,
+        }
+    }
+
+    pub(crate) fn attributes(&self, monitor: Option<MonitorHandle>) -> WindowAttributes {
+        let mut attributes = WinitWindow::default_attributes()
+            .with_title(&self.title)
+            .with_resizable(self.resizable)
+            .with_decorations(self.decorations)
+            .with_fullscreen(self.mode.to_fullscreen(monitor));
+
+        if self.width > 0. && self.height > 0. {
+            attributes = attributes.with_inner_size(PhysicalSize::new(self.width, self.height));
+        }
+
+        if let Some(position) = self.position {
+            attributes = attributes.with_position(PhysicalPosition::new(position.x, position.y));
+        }
+
+        attributes
+    }
+
+    /// The ratio between physical and logical pixels
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Returns if the window has keyboard focus
+    pub fn focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Applies the fields that changed since `previous` to the winit window
+    pub(crate) fn apply_changes(&self, previous: &Window, window: &WinitWindow) {
+        if self.title != previous.title {
+            window.set_title(&self.title);
+        }
+
+        if self.mode != previous.mode {
+            window.set_fullscreen(self.mode.to_fullscreen(window.current_monitor()));
+        }
+
+        if (self.width, self.height) != (previous.width, previous.height) {
+            let _ = window.request_inner_size(PhysicalSize::new(
+                self.width.max(1.) as u32,
+                self.height.max(1.) as u32,
+            ));
         }
+
+        if self.position != previous.position
+            && let Some(position) = self.position
+        {
+            window.set_outer_position(PhysicalPosition::new(position.x, position.y));
+        }
+
+        if self.resizable != previous.resizable {
+            window.set_resizable(self.resizable);
+        }
+
+        if self.decorations != previous.decorations {
+            window.set_decorations(self.decorations);
+        }
+
+        if self.cursor_visible != previous.cursor_visible {
+            window.set_cursor_visible(self.cursor_visible);
+        }
+
+        if self.cursor_grab != previous.cursor_grab
+            && let Err(e) = window.set_cursor_grab(self.cursor_grab)
+        {
+            tracing::error!("Failed to set cursor grab mode {:?}: {e}", self.cursor_grab);
+        }
+    }
+}
+
+impl Component for Window {}
+
+/// Sent when the window is moved, the position is of the top-left corner in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowMoved {
+    pub window: WindowRef,
+    pub position: IVec2,
+}
+
+impl Event for WindowMoved {}
+
+/// Sent when the window gains or loses keyboard focus
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowFocused {
+    pub window: WindowRef,
+    pub focused: bool,
+}
+
+impl Event for WindowFocused {}
+
+/// Sent when the window moves to a monitor with a different DPI or the DPI setting changes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleFactorChanged {
+    pub window: WindowRef,
+    pub scale_factor: f64,
+}
+
+impl Event for ScaleFactorChanged {}
+
+/// Sent when the user tries to close a window
+///
+/// For the primary window the app only exits on its own if [`WindowDescriptor::exit_on_close`] is
+/// set, other windows are closed right away
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CloseRequested {
+    pub window: WindowRef,
+}
+
+impl Event for CloseRequested {}
+
+#[cfg(test)]
+mod tests {
+    use winit::{dpi::PhysicalPosition, event::WindowEvent};
+
+    use crate::State;
+
+    use super::*;
+
+    #[test]
+    fn test_window_events_update_resource() {
+        let mut state = State::new();
+        state.initialize();
+
+        state.handle_window_input(
+            WindowRef::Primary,
+            WindowEvent::Moved(PhysicalPosition::new(10, 20)),
+        );
+        state.handle_window_input(WindowRef::Primary, WindowEvent::Focused(true));
+
+        let window = state.world.read_resource::<Window>().unwrap();
+        assert_eq!(window.position, Some(IVec2::new(10, 20)));
+        assert!(window.focused());
+    }
+
+    #[test]
+    fn test_close_requested_exits() {
+        let mut state = State::new();
+        state.initialize();
+
+        assert!(!state.run_frame());
+
+        state.handle_window_input(WindowRef::Primary, WindowEvent::CloseRequested);
+
+        assert!(state.run_frame());
+    }
+
+    #[test]
+    fn test_close_requested_without_exit_on_close() {
+        let mut state = State::new();
+        state.world.insert_resource(WindowDescriptor {
+            exit_on_close: false,
+            ..Default::default()
+        });
+        state.initialize();
+
+        state.handle_window_input(WindowRef::Primary, WindowEvent::CloseRequested);
+
+        assert!(!state.run_frame());
+    }
+
+    #[test]
+    fn test_secondary_window() {
+        let mut state = State::new();
+        state.initialize();
+
+        let entity = state.world.add_entity((Window::new(),));
+        let window = WindowRef::Entity(entity);
+
+        state.handle_window_input(window, WindowEvent::Moved(PhysicalPosition::new(10, 20)));
+
+        assert_eq!(
+            state
+                .world
+                .get_component::<Window>(entity)
+                .unwrap()
+                .position,
+            Some(IVec2::new(10, 20))
+        );
+        assert_eq!(
+            state.world.read_resource::<Window>().unwrap().position,
+            None
+        );
+
+        state.handle_window_input(window, WindowEvent::CloseRequested);
+
+        assert!(state.world.get_component::<Window>(entity).is_none());
+        assert!(!state.run_frame());
     }
 }